@@ -0,0 +1,57 @@
+// Aggression scaling - per-enemy `aggression` times a global (wave) multiplier
+use std::cell::Cell;
+
+use crate::types::*;
+
+pub struct AggressionConfig;
+
+impl AggressionConfig {
+    pub const MIN: f64 = 0.25;
+    pub const MAX: f64 = 3.0;
+    pub const PER_WAVE: f64 = 0.04;
+}
+
+thread_local! {
+    static GLOBAL_AGGRESSION: Cell<f64> = const { Cell::new(1.0) };
+}
+
+// Finite values are clamped to [MIN, MAX]; NaN and infinities are rejected
+pub fn set_global(value: f64) -> Result<(), String> {
+    if !value.is_finite() {
+        return Err(format!("global aggression = {} is not a finite number", value));
+    }
+    GLOBAL_AGGRESSION.with(|g| g.set(clamp(value, AggressionConfig::MIN, AggressionConfig::MAX)));
+    Ok(())
+}
+
+pub fn global() -> f64 {
+    GLOBAL_AGGRESSION.with(|g| g.get())
+}
+
+// Linear ramp: wave 1 = 1.0, +PER_WAVE per wave after that
+pub fn for_wave(wave: u32) -> f64 {
+    let ramp = 1.0 + wave.saturating_sub(1) as f64 * AggressionConfig::PER_WAVE;
+    clamp(ramp, AggressionConfig::MIN, AggressionConfig::MAX)
+}
+
+// Multiplier applied to thrust, charge probability, dodge force and fire rate
+#[inline]
+pub fn effective(enemy: &EnemyState) -> f64 {
+    clamp(enemy.aggression * global(), AggressionConfig::MIN, AggressionConfig::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_rejects_non_finite_values() {
+        set_global(2.0).unwrap();
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(set_global(value).is_err());
+        }
+        assert_eq!(global(), 2.0);
+        set_global(10.0).unwrap();
+        assert_eq!(global(), AggressionConfig::MAX);
+    }
+}
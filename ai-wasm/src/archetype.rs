@@ -0,0 +1,267 @@
+// Enemy archetypes - data-driven enemy definitions loaded from JSON
+// An enemy's type_id (offset 4) is an index into the loaded archetype list.
+use std::cell::RefCell;
+use serde::{Serialize, Deserialize};
+
+use crate::split::SplitRule;
+
+pub type ArchetypeId = usize;
+
+// Built-in definitions, matching the original basic / elite / rammer / exploder
+const DEFAULT_ARCHETYPES: &str = include_str!("archetypes.json");
+
+// Which update routine drives the enemy
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Movement {
+    // Wanders forward, turning by turn_rate (+/- wander_jitter) each frame
+    Circle,
+    // Heads for its target until within keep_distance
+    Chase,
+    // Physics-driven charger tuned by RammerConfig (speed is unused)
+    Ram,
+    // Chases its target and detonates on contact
+    Explode,
+}
+
+// `count` bullets fanned out `spread` radians apart, aimed at the nearest target
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirePattern {
+    #[serde(default = "FirePattern::default_count")]
+    pub count: u32,
+    #[serde(default)]
+    pub spread: f64,
+    pub cooldown: f64,
+    pub damage: f64,
+    pub bullet_speed: f64,
+}
+
+impl FirePattern {
+    fn default_count() -> u32 {
+        1
+    }
+}
+
+// Chance of each pickup when the enemy is killed by the player
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DropRates {
+    #[serde(default)]
+    pub powerup: f64,
+    #[serde(default)]
+    pub module_star: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Archetype {
+    pub name: String,
+    pub movement: Movement,
+    pub speed: f64,
+    #[serde(default)]
+    pub turn_rate: f64,
+    // Random extra turn per frame (circle movement), +/- radians
+    #[serde(default)]
+    pub wander_jitter: f64,
+    #[serde(default)]
+    pub keep_distance: f64,
+    pub hp: f64,
+    pub radius: f64,
+    // Body collisions (bodies.rs): share of the push-apart and impulse, and bounciness
+    #[serde(default = "Archetype::default_mass")]
+    pub mass: f64,
+    #[serde(default = "Archetype::default_restitution")]
+    pub restitution: f64,
+    // Circle movers only: steer with nearby flockers of the same faction (flock.rs)
+    #[serde(default)]
+    pub flock: bool,
+    #[serde(default)]
+    pub fire: Option<FirePattern>,
+    #[serde(default)]
+    pub score: f64,
+    #[serde(default)]
+    pub drops: DropRates,
+    #[serde(default)]
+    pub split: SplitRule,
+}
+
+impl Archetype {
+    fn default_mass() -> f64 {
+        1.0
+    }
+
+    fn default_restitution() -> f64 {
+        0.5
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let check = |field: &str, value: f64, min: f64, max: f64| -> Result<(), String> {
+            if value.is_finite() && value >= min && value <= max {
+                Ok(())
+            } else {
+                Err(format!("archetype '{}': {} = {} is outside [{}, {}]", self.name, field, value, min, max))
+            }
+        };
+
+        if self.name.is_empty() {
+            return Err("archetype name must not be empty".to_string());
+        }
+        check("speed", self.speed, 0.0, 100.0)?;
+        check("turnRate", self.turn_rate, -std::f64::consts::PI, std::f64::consts::PI)?;
+        check("wanderJitter", self.wander_jitter, 0.0, std::f64::consts::PI)?;
+        check("keepDistance", self.keep_distance, 0.0, 5000.0)?;
+        check("hp", self.hp, 1.0, 1.0e6)?;
+        check("radius", self.radius, 1.0, 1000.0)?;
+        check("mass", self.mass, 0.01, 1000.0)?;
+        check("restitution", self.restitution, 0.0, 3.0)?;
+        check("score", self.score, 0.0, 1.0e6)?;
+        check("drops.powerup", self.drops.powerup, 0.0, 1.0)?;
+        check("drops.moduleStar", self.drops.module_star, 0.0, 1.0)?;
+        if let Some(fire) = &self.fire {
+            check("fire.count", fire.count as f64, 1.0, 64.0)?;
+            check("fire.spread", fire.spread, 0.0, std::f64::consts::PI)?;
+            check("fire.cooldown", fire.cooldown, 1.0, 6000.0)?;
+            check("fire.damage", fire.damage, 0.0, 1000.0)?;
+            check("fire.bulletSpeed", fire.bullet_speed, 0.1, 100.0)?;
+        }
+        check("split.children", self.split.children as f64, 0.0, 8.0)?;
+        check("split.maxDepth", self.split.max_depth as f64, 0.0, 4.0)?;
+        Ok(())
+    }
+}
+
+// Parse and validate a JSON array of archetypes
+pub fn parse(json: &str) -> Result<Vec<Archetype>, String> {
+    let archetypes: Vec<Archetype> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    if archetypes.is_empty() {
+        return Err("archetype list must not be empty".to_string());
+    }
+    for (i, archetype) in archetypes.iter().enumerate() {
+        archetype.validate()?;
+        if archetypes[..i].iter().any(|other| other.name == archetype.name) {
+            return Err(format!("duplicate archetype '{}'", archetype.name));
+        }
+    }
+    Ok(archetypes)
+}
+
+fn defaults() -> Vec<Archetype> {
+    parse(DEFAULT_ARCHETYPES).expect("built-in archetypes.json is valid")
+}
+
+thread_local! {
+    static ARCHETYPES: RefCell<Vec<Archetype>> = RefCell::new(defaults());
+}
+
+// Replaces the whole list; ids follow array order
+pub fn load(json: &str) -> Result<usize, String> {
+    let archetypes = parse(json)?;
+    let count = archetypes.len();
+    ARCHETYPES.with(|a| *a.borrow_mut() = archetypes);
+    Ok(count)
+}
+
+pub fn reset() {
+    ARCHETYPES.with(|a| *a.borrow_mut() = defaults());
+}
+
+// Borrow the loaded list for the duration of `f` (one borrow per tick)
+pub fn with_archetypes<R>(f: impl FnOnce(&[Archetype]) -> R) -> R {
+    ARCHETYPES.with(|a| f(&a.borrow()))
+}
+
+pub fn find(name: &str) -> Option<ArchetypeId> {
+    with_archetypes(|list| list.iter().position(|a| a.name == name))
+}
+
+// Unknown ids fall back to the first archetype
+#[inline]
+pub fn lookup(list: &[Archetype], id: ArchetypeId) -> &Archetype {
+    list.get(id).unwrap_or(&list[0])
+}
+
+pub fn split_rule(id: ArchetypeId) -> SplitRule {
+    with_archetypes(|list| lookup(list, id).split)
+}
+
+// Returns false for an unknown id
+pub fn set_flocking(id: ArchetypeId, enabled: bool) -> bool {
+    ARCHETYPES.with(|a| match a.borrow_mut().get_mut(id) {
+        Some(archetype) => {
+            archetype.flock = enabled;
+            true
+        }
+        None => false,
+    })
+}
+
+// Rejects an unknown id or a rule outside the validate() bounds, leaving the old rule in place
+pub fn set_split_rule(id: ArchetypeId, rule: SplitRule) -> Result<(), String> {
+    ARCHETYPES.with(|a| {
+        let mut list = a.borrow_mut();
+        let archetype = list.get_mut(id).ok_or_else(|| format!("unknown archetype id {}", id))?;
+        let updated = Archetype { split: rule, ..archetype.clone() };
+        updated.validate()?;
+        *archetype = updated;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINIMAL: &str = r#"{ "name": "drone", "movement": "chase", "speed": 1.5, "hp": 20, "radius": 10 }"#;
+
+    #[test]
+    fn built_in_archetypes_parse() {
+        let list = parse(DEFAULT_ARCHETYPES).unwrap();
+        let names: Vec<&str> = list.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["basic", "elite", "rammer", "exploder"]);
+
+        let basic = &list[0];
+        assert_eq!(basic.movement, Movement::Circle);
+        assert_eq!((basic.speed, basic.turn_rate, basic.hp), (2.0, 0.02, 50.0));
+        assert!(basic.flock);
+        assert_eq!(list[1].keep_distance, 200.0);
+    }
+
+    #[test]
+    fn omitted_fields_take_defaults() {
+        let list = parse(&format!("[{}]", MINIMAL)).unwrap();
+        let drone = &list[0];
+        assert_eq!((drone.mass, drone.restitution), (1.0, 0.5));
+        assert_eq!(drone.fire, None);
+        assert_eq!(drone.split, SplitRule::default());
+        assert!(!drone.flock);
+    }
+
+    #[test]
+    fn malformed_lists_are_rejected() {
+        let cases = [
+            "[]".to_string(),
+            format!("[{}, {}]", MINIMAL, MINIMAL),
+            format!("[{}]", MINIMAL.replace("chase", "teleport")),
+            format!("[{}]", MINIMAL.replace(r#""hp": 20, "#, "")),
+            format!("[{}]", MINIMAL.replace("1.5", "-1")),
+            format!("[{}]", MINIMAL.replace("drone", "")),
+            format!("[{}]", MINIMAL.replace(r#""radius": 10"#, r#""radius": 10, "fire": { "cooldown": 0, "damage": 1, "bulletSpeed": 4 }"#)),
+        ];
+        for json in &cases {
+            assert!(parse(json).is_err(), "accepted {}", json);
+        }
+    }
+
+    #[test]
+    fn load_replaces_the_list_until_reset() {
+        assert_eq!(load(&format!("[{}]", MINIMAL)), Ok(1));
+        assert_eq!(find("drone"), Some(0));
+        assert_eq!(find("basic"), None);
+        assert!(load("[]").is_err());
+        assert_eq!(find("drone"), Some(0));
+
+        reset();
+        assert_eq!(find("basic"), Some(0));
+    }
+}
@@ -0,0 +1,236 @@
+// Enemy body collisions - resolved for every type after all enemies have moved
+// Overlapping pairs are pushed apart in inverse proportion to their mass (archetype "mass")
+// and exchange an impulse along the contact normal using the bouncier restitution.
+// Only physics-driven rammers carry velocity; circle movers bounce their heading instead,
+// chase and exploder movement re-aims every frame so they only get the positional push.
+use std::cell::RefCell;
+
+use crate::archetype::Movement;
+use crate::config::rammer_config;
+use crate::events::*;
+use crate::fsm::{transition, AiMode};
+use crate::grid::EnemyGrid;
+use crate::steering::Vec2;
+use crate::types::*;
+
+pub struct BodyConfig;
+
+impl BodyConfig {
+    // Fraction of the overlap removed per tick
+    pub const CORRECTION: f64 = 0.8;
+    // Overlap left alone so resting neighbours do not jitter
+    pub const SLOP: f64 = 0.5;
+    // Broadphase cell size; grown to the largest diameter so a 3x3 query covers every contact
+    pub const MIN_CELL: f64 = 128.0;
+}
+
+// Broadphase buffers, cleared and refilled every tick instead of reallocated
+struct BodyScratch {
+    positions: Vec<f64>,
+    grid: EnemyGrid,
+    neighbors: Vec<usize>,
+}
+
+thread_local! {
+    static SCRATCH: RefCell<BodyScratch> = RefCell::new(BodyScratch {
+        positions: Vec::new(),
+        grid: EnemyGrid::new(BodyConfig::MIN_CELL),
+        neighbors: Vec::new(),
+    });
+}
+
+pub fn resolve_bodies(enemy_data: &mut [f64], ctx: &TickContext, events: &mut Vec<AiEvent>) {
+    let num_enemies = enemy_data.len() / ENEMY_STRIDE;
+    if num_enemies < 2 {
+        return;
+    }
+
+    SCRATCH.with(|scratch| {
+        let BodyScratch { positions, grid, neighbors } = &mut *scratch.borrow_mut();
+        positions.clear();
+        let mut max_radius: f64 = 0.0;
+        for record in enemy_data.chunks_exact(ENEMY_STRIDE) {
+            positions.extend_from_slice(&[record[0], record[1]]);
+            max_radius = max_radius.max(record[7]);
+        }
+        grid.set_cell_size(BodyConfig::MIN_CELL.max(max_radius * 2.0));
+        grid.rebuild(positions);
+
+        for i in 0..num_enemies {
+            grid.query_neighbors_into(positions[i * 2], positions[i * 2 + 1], neighbors);
+            for &j in neighbors.iter() {
+                if j > i {
+                    resolve_pair(i, j, enemy_data, ctx, events);
+                }
+            }
+        }
+    });
+}
+
+fn resolve_pair(i: usize, j: usize, enemy_data: &mut [f64], ctx: &TickContext, events: &mut Vec<AiEvent>) {
+    let (oi, oj) = (i * ENEMY_STRIDE, j * ENEMY_STRIDE);
+    let mut a = EnemyState::from_slice(&enemy_data[oi..oi + ENEMY_STRIDE]);
+    let mut b = EnemyState::from_slice(&enemy_data[oj..oj + ENEMY_STRIDE]);
+    if a.hp <= 0.0 || b.hp <= 0.0 {
+        return;
+    }
+
+    let offset = Vec2::new(a.x - b.x, a.y - b.y);
+    let dist = offset.length();
+    let min_dist = a.radius + b.radius;
+    if dist <= 0.0 || dist >= min_dist {
+        return;
+    }
+
+    let arch_a = ctx.archetype(a.enemy_type);
+    let arch_b = ctx.archetype(b.enemy_type);
+    let inv_a = 1.0 / arch_a.mass;
+    let inv_b = 1.0 / arch_b.mass;
+    let inv_sum = inv_a + inv_b;
+    // Normal from b to a
+    let n = offset * (1.0 / dist);
+
+    // Positional correction
+    let push = (min_dist - dist - BodyConfig::SLOP).max(0.0) * BodyConfig::CORRECTION / inv_sum;
+    a.x += n.x * push * inv_a;
+    a.y += n.y * push * inv_a;
+    b.x -= n.x * push * inv_b;
+    b.y -= n.y * push * inv_b;
+
+    // Impulse along the normal when approaching
+    let vel_a = body_velocity(&a, arch_a.movement, a.speed(arch_a));
+    let vel_b = body_velocity(&b, arch_b.movement, b.speed(arch_b));
+    let closing = (vel_a - vel_b).dot(n);
+    if closing < 0.0 {
+        let restitution = arch_a.restitution.max(arch_b.restitution);
+        let impulse = -(1.0 + restitution) * closing / inv_sum;
+        bounce(i, &mut a, arch_a.movement, n, n * (impulse * inv_a), events);
+        bounce(j, &mut b, arch_b.movement, -n, n * (-impulse * inv_b), events);
+    }
+
+    for (index, enemy, offset) in [(i, &a, oi), (j, &b, oj)] {
+        let was_alive = enemy_data[offset + 8] > 0.0;
+        enemy.write_to_slice(&mut enemy_data[offset..offset + ENEMY_STRIDE]);
+        if was_alive && enemy.hp <= 0.0 {
+            events.push(AiEvent::Killed { index, x: enemy.x, y: enemy.y, faction: enemy.faction });
+        }
+    }
+}
+
+fn body_velocity(enemy: &EnemyState, movement: Movement, speed: f64) -> Vec2 {
+    match movement {
+        Movement::Ram => Vec2::new(enemy.vx, enemy.vy),
+        Movement::Circle => Vec2::from_angle(enemy.angle) * speed,
+        Movement::Chase | Movement::Explode => Vec2::ZERO,
+    }
+}
+
+// Apply the impulse `dv` to a body pushed along `normal` (pointing away from the other body)
+fn bounce(index: usize, enemy: &mut EnemyState, movement: Movement, normal: Vec2, dv: Vec2, events: &mut Vec<AiEvent>) {
+    match movement {
+        Movement::Ram => {
+            let cfg = rammer_config();
+            let vel = (Vec2::new(enemy.vx, enemy.vy) + dv).truncate(cfg.boost_max);
+            enemy.vx = vel.x;
+            enemy.vy = vel.y;
+
+            // Same bookkeeping as a wall bounce: boost, hit cooldown and self damage
+            if enemy.hit_cooldown <= 0.0 {
+                enemy.bounce_boost_frames = cfg.boost_frames;
+                enemy.hit_cooldown = cfg.hit_cd;
+                enemy.hp -= cfg.hit_damage;
+                events.push(AiEvent::SelfDamage { index, amount: cfg.hit_damage, cause: DamageCause::EnemyCollision });
+                if matches!(enemy.ai_mode, AiMode::Cruise | AiMode::OrbitBreak) {
+                    transition(index, enemy, AiMode::Rebound, events);
+                }
+            }
+        }
+        Movement::Circle => {
+            // Reflect the heading if it points into the other body
+            let heading = Vec2::from_angle(enemy.angle);
+            let into = heading.dot(normal);
+            if into < 0.0 {
+                let reflected = heading - normal * (2.0 * into);
+                enemy.angle = reflected.y.atan2(reflected.x);
+            }
+        }
+        Movement::Chase | Movement::Explode => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archetype;
+
+    // (archetype, x, y, vx, vy) records, alive, in the enemy faction
+    fn buffer(bodies: &[(&str, f64, f64, f64, f64)]) -> Vec<f64> {
+        let mut data = Vec::new();
+        for &(name, x, y, vx, vy) in bodies {
+            let id = archetype::find(name).unwrap();
+            let (radius, hp) = archetype::with_archetypes(|list| (list[id].radius, list[id].hp));
+            let mut record = [0.0; ENEMY_STRIDE];
+            record[..5].copy_from_slice(&[x, y, vx, vy, id as f64]);
+            record[5] = faction::ENEMY as f64;
+            record[7] = radius;
+            record[8] = hp;
+            record[9] = hp;
+            data.extend_from_slice(&record);
+        }
+        data
+    }
+
+    #[test]
+    fn overlap_is_pushed_apart_by_inverse_mass() {
+        // Elite (mass 3, radius 25) and basic (mass 1, radius 15) overlapping by 10
+        let mut enemy_data = buffer(&[("elite", 300.0, 300.0, 0.0, 0.0), ("basic", 330.0, 300.0, 0.0, 0.0)]);
+        let mut events = Vec::new();
+        archetype::with_archetypes(|archetypes| {
+            resolve_bodies(&mut enemy_data, &TickContext::for_test(archetypes), &mut events);
+        });
+
+        let elite_dx = enemy_data[0] - 300.0;
+        let basic_dx = enemy_data[ENEMY_STRIDE] - 330.0;
+        assert!(elite_dx < 0.0 && basic_dx > 0.0);
+        assert!((basic_dx / elite_dx + 3.0).abs() < 1e-9);
+        let push = (10.0 - BodyConfig::SLOP) * BodyConfig::CORRECTION;
+        assert!((basic_dx - elite_dx - push).abs() < 1e-9);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn rammers_colliding_head_on_take_damage_and_rebound() {
+        let mut enemy_data = buffer(&[("rammer", 300.0, 300.0, 6.0, 0.0), ("rammer", 330.0, 300.0, -6.0, 0.0)]);
+        let mut events = Vec::new();
+        archetype::with_archetypes(|archetypes| {
+            resolve_bodies(&mut enemy_data, &TickContext::for_test(archetypes), &mut events);
+        });
+
+        let cfg = rammer_config();
+        for (index, record) in enemy_data.chunks_exact(ENEMY_STRIDE).enumerate() {
+            let enemy = EnemyState::from_slice(record);
+            assert_eq!(enemy.hp, enemy.max_hp - cfg.hit_damage);
+            assert_eq!(enemy.ai_mode, AiMode::Rebound);
+            assert_eq!(enemy.hit_cooldown, cfg.hit_cd);
+            assert!(events.contains(&AiEvent::SelfDamage {
+                index,
+                amount: cfg.hit_damage,
+                cause: DamageCause::EnemyCollision,
+            }));
+        }
+        // Velocities reversed: the pair now separates
+        assert!(enemy_data[2] < 0.0 && enemy_data[ENEMY_STRIDE + 2] > 0.0);
+    }
+
+    #[test]
+    fn hit_cooldown_suppresses_repeat_damage() {
+        let mut enemy_data = buffer(&[("rammer", 300.0, 300.0, 6.0, 0.0), ("rammer", 330.0, 300.0, -6.0, 0.0)]);
+        enemy_data[13] = 2.0;
+        enemy_data[ENEMY_STRIDE + 13] = 2.0;
+        let mut events = Vec::new();
+        archetype::with_archetypes(|archetypes| {
+            resolve_bodies(&mut enemy_data, &TickContext::for_test(archetypes), &mut events);
+        });
+        assert!(events.is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+// Runtime tuning tables shared by the AI update
+use std::cell::Cell;
+
+use crate::flock::FlockConfig;
+use crate::squad::SquadConfig;
+use crate::types::*;
+
+thread_local! {
+    static RAMMER_CONFIG: Cell<RammerConfig> = Cell::new(RammerConfig::default());
+    static FLOCK_CONFIG: Cell<FlockConfig> = Cell::new(FlockConfig::default());
+    static SQUAD_CONFIG: Cell<SquadConfig> = Cell::new(SquadConfig::default());
+}
+
+pub fn rammer_config() -> RammerConfig {
+    RAMMER_CONFIG.with(|c| c.get())
+}
+
+pub fn set_rammer_config(config: RammerConfig) -> Result<(), String> {
+    config.validate()?;
+    RAMMER_CONFIG.with(|c| c.set(config));
+    Ok(())
+}
+
+pub fn reset_rammer_config() {
+    RAMMER_CONFIG.with(|c| c.set(RammerConfig::default()));
+}
+
+pub fn flock_config() -> FlockConfig {
+    FLOCK_CONFIG.with(|c| c.get())
+}
+
+pub fn set_flock_config(config: FlockConfig) -> Result<(), String> {
+    config.validate()?;
+    FLOCK_CONFIG.with(|c| c.set(config));
+    Ok(())
+}
+
+pub fn reset_flock_config() {
+    FLOCK_CONFIG.with(|c| c.set(FlockConfig::default()));
+}
+
+pub fn squad_config() -> SquadConfig {
+    SQUAD_CONFIG.with(|c| c.get())
+}
+
+pub fn set_squad_config(config: SquadConfig) -> Result<(), String> {
+    config.validate()?;
+    SQUAD_CONFIG.with(|c| c.set(config));
+    Ok(())
+}
+
+pub fn reset_squad_config() {
+    SQUAD_CONFIG.with(|c| c.set(SquadConfig::default()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_rammer_config_is_rejected_and_not_stored() {
+        let cases = [
+            RammerConfig { damp: f64::NAN, ..RammerConfig::default() },
+            RammerConfig { charge_prob: 1.5, ..RammerConfig::default() },
+            RammerConfig { base_max: 30.0, boost_max: 20.0, ..RammerConfig::default() },
+        ];
+        for config in cases {
+            assert!(set_rammer_config(config).is_err());
+        }
+        assert_eq!(rammer_config(), RammerConfig::default());
+    }
+
+    #[test]
+    fn errors_name_the_offending_field() {
+        let err = RammerConfig { tip_align: 2.0, ..RammerConfig::default() }.validate().unwrap_err();
+        assert_eq!(err, "RammerConfig.TIP_ALIGN = 2 is outside [-1, 1]");
+    }
+
+    #[test]
+    fn valid_rammer_config_is_stored() {
+        let config = RammerConfig { thrust: 2.0, ..RammerConfig::default() };
+        set_rammer_config(config).unwrap();
+        assert_eq!(rammer_config().thrust, 2.0);
+        reset_rammer_config();
+    }
+}
@@ -1,100 +1,235 @@
 // Enemy AI behaviors - complete port from JavaScript to Rust
 use crate::types::*;
-use crate::grid::*;
-
-// Random number generation using js_sys
-fn random() -> f64 {
-    js_sys::Math::random()
-}
+use crate::events::*;
+use rng::random;
+use crate::aggression;
+use crate::config::{self, rammer_config};
+use crate::archetype::{Archetype, FirePattern, Movement};
+use crate::steering::*;
+use crate::fsm::{next_rammer_mode, transition, AiMode};
+use crate::flock::{self, FlockSnapshot};
+use crate::squad::{self, SquadPlan};
+use faction::FactionId;
 
 pub fn update_single_enemy(
+    index: usize,
     mut enemy: EnemyState,
-    ship_x: f64,
-    ship_y: f64,
-    ship_radius: f64,
-    module_data: &[f64],
-    projectile_data: &[f64],
+    ctx: &TickContext,
     enemy_data: &[f64],
-    canvas_width: f64,
-    canvas_height: f64,
-    shield_active: bool,
+    flock: Option<&FlockSnapshot>,
+    squads: &SquadPlan,
+    events: &mut Vec<AiEvent>,
 ) -> EnemyState {
+    // Dead records (e.g. killed by AreaDamage earlier this tick) stay in the buffer until the host drops them
+    if enemy.hp <= 0.0 {
+        return enemy;
+    }
+
     // Zombie lifetime countdown
-    if enemy.is_zombie {
+    if enemy.faction == faction::ZOMBIE {
         enemy.zombie_lifetime -= 1.0;
         if enemy.zombie_lifetime <= 0.0 {
             enemy.hp = 0.0;
+            events.push(AiEvent::ZombieExpired { index, x: enemy.x, y: enemy.y });
             return enemy;
         }
     }
 
-    match enemy.enemy_type {
-        EnemyType::Basic => update_basic(&mut enemy),
-        EnemyType::Elite => update_elite(&mut enemy, ship_x, ship_y, enemy_data),
-        EnemyType::Rammer => update_rammer(&mut enemy, ship_x, ship_y, ship_radius, projectile_data, enemy_data, canvas_width, canvas_height, shield_active),
-        EnemyType::Exploder => update_exploder(&mut enemy, ship_x, ship_y, ship_radius, module_data, enemy_data, canvas_width, canvas_height, shield_active),
+    let first_event = events.len();
+    enemy.state_frames += 1.0;
+
+    update_stealth(&mut enemy);
+
+    let archetype = ctx.archetype(enemy.enemy_type);
+
+    // Squad members fly to their slot until they engage; the slot 0 leader flies freely
+    let in_formation = enemy.squad != 0 && enemy.squad_slot != 0 && {
+        let target = find_nearest_target(index, &enemy, ctx, enemy_data);
+        let speed = enemy.speed(archetype);
+        squad::follow(&mut enemy, speed, target, ctx, enemy_data, squads, &config::squad_config())
+    };
+
+    if !in_formation {
+        match archetype.movement {
+            Movement::Circle => update_circle(index, &mut enemy, archetype, flock),
+            Movement::Chase => update_chase(index, &mut enemy, archetype, ctx, enemy_data),
+            Movement::Ram => update_rammer(index, &mut enemy, ctx, enemy_data, events),
+            Movement::Explode => update_exploder(index, &mut enemy, archetype, ctx, enemy_data, events),
+        }
     }
 
-    // Boundary adjustments for non-rammer and non-exploder
-    if enemy.enemy_type != EnemyType::Rammer && enemy.enemy_type != EnemyType::Exploder {
-        if enemy.x < enemy.radius || enemy.x > canvas_width - enemy.radius {
-            enemy.angle = std::f64::consts::PI - enemy.angle;
+    // Boundary adjustments (rammers bounce inside update_rammer)
+    match archetype.movement {
+        Movement::Circle | Movement::Chase => {
+            if enemy.x < enemy.radius || enemy.x > ctx.canvas_width - enemy.radius {
+                enemy.angle = std::f64::consts::PI - enemy.angle;
+            }
+            if enemy.y < enemy.radius || enemy.y > ctx.canvas_height - enemy.radius {
+                enemy.angle = -enemy.angle;
+            }
         }
-        if enemy.y < enemy.radius || enemy.y > canvas_height - enemy.radius {
-            enemy.angle = -enemy.angle;
+        Movement::Explode => {
+            enemy.x = clamp(enemy.x, enemy.radius, ctx.canvas_width - enemy.radius);
+            enemy.y = clamp(enemy.y, enemy.radius, ctx.canvas_height - enemy.radius);
         }
+        Movement::Ram => {}
     }
 
-    // Boundary adjust for exploder
-    if enemy.enemy_type == EnemyType::Exploder {
-        enemy.x = clamp(enemy.x, enemy.radius, canvas_width - enemy.radius);
-        enemy.y = clamp(enemy.y, enemy.radius, canvas_height - enemy.radius);
+    // Shooting logic
+    if let Some(fire) = &archetype.fire {
+        if enemy.shoot_cooldown <= 0.0 {
+            shoot(index, &enemy, fire, ctx, enemy_data, events);
+            enemy.shoot_cooldown = (fire.cooldown / aggression::effective(&enemy)).round();
+        }
+        enemy.shoot_cooldown -= 1.0;
     }
 
-    // Update shoot cooldown
-    if enemy.shoot_cooldown > 0.0 {
-        enemy.shoot_cooldown -= 1.0;
+    // A detonation is reported once, as Exploded, so the host does not score the exploder twice
+    let detonated = events[first_event..]
+        .iter()
+        .any(|event| matches!(event, AiEvent::Exploded { index: source, .. } if *source == index));
+    if enemy.hp <= 0.0 && !detonated {
+        events.push(AiEvent::Killed { index, x: enemy.x, y: enemy.y, faction: enemy.faction });
     }
 
     enemy
 }
 
-fn update_basic(enemy: &mut EnemyState) {
-    enemy.angle += 0.02;
-    enemy.x += enemy.angle.cos() * enemy.radius * 0.133; // speed=2 for radius=15
-    enemy.y += enemy.angle.sin() * enemy.radius * 0.133;
+fn shoot(
+    index: usize,
+    enemy: &EnemyState,
+    fire: &FirePattern,
+    ctx: &TickContext,
+    enemy_data: &[f64],
+    events: &mut Vec<AiEvent>,
+) {
+    let Some((target_x, target_y)) = find_nearest_target(index, enemy, ctx, enemy_data) else {
+        return;
+    };
+
+    let angle = (target_y - enemy.y).atan2(target_x - enemy.x);
+
+    // Fan centered on the target: count=5, spread=s fires at -2s, -s, 0, s, 2s
+    let center = (fire.count as f64 - 1.0) / 2.0;
+    for k in 0..fire.count {
+        let a = angle + (k as f64 - center) * fire.spread;
+        events.push(AiEvent::Shot {
+            source: index,
+            x: enemy.x,
+            y: enemy.y,
+            vx: a.cos() * fire.bullet_speed,
+            vy: a.sin() * fire.bullet_speed,
+            damage: fire.damage,
+            faction: enemy.faction,
+        });
+    }
 }
 
-fn update_elite(enemy: &mut EnemyState, ship_x: f64, ship_y: f64, enemy_data: &[f64]) {
-    let (target_x, target_y) = if enemy.is_zombie {
-        find_nearest_hostile(enemy.x, enemy.y, enemy_data)
-            .unwrap_or((ship_x, ship_y))
-    } else {
-        (ship_x, ship_y)
-    };
+// Nearest thing a shooter is hostile to: the ship and modules, then other enemies
+fn find_nearest_target(index: usize, enemy: &EnemyState, ctx: &TickContext, enemy_data: &[f64]) -> Option<(f64, f64)> {
+    let (x, y) = (enemy.x, enemy.y);
+    let mut best_dist_sq = f64::INFINITY;
+    let mut result = None;
 
-    let dx = target_x - enemy.x;
-    let dy = target_y - enemy.y;
-    let dist = hypot(dx, dy);
+    if ctx.hunts_ship(enemy.faction) {
+        let dx = ctx.ship_x - x;
+        let dy = ctx.ship_y - y;
+        best_dist_sq = dx * dx + dy * dy;
+        result = Some((ctx.ship_x, ctx.ship_y));
+
+        // Module data stride: [x, y, radius]
+        for module in ctx.module_data.chunks_exact(3) {
+            let dx = module[0] - x;
+            let dy = module[1] - y;
+            let dist_sq = dx * dx + dy * dy;
+            if dist_sq < best_dist_sq {
+                best_dist_sq = dist_sq;
+                result = Some((module[0], module[1]));
+            }
+        }
+    }
 
-    if dist > 200.0 {
-        let speed = enemy.radius * 0.04; // speed=1 for radius=25
-        enemy.x += (dx / dist) * speed;
-        enemy.y += (dy / dist) * speed;
+    match find_nearest_hostile(index, enemy.faction, x, y, ctx, enemy_data) {
+        Some((ex, ey, dist_sq)) if dist_sq < best_dist_sq => Some((ex, ey)),
+        _ => result,
     }
 }
 
+// Cloaked enemies shimmer around a low visibility and decloak while winding up or charging
+fn update_stealth(enemy: &mut EnemyState) {
+    if !enemy.is_stealth {
+        enemy.visibility = 1.0;
+        return;
+    }
+
+    let cloaked = StealthConfig::CLOAKED_VISIBILITY + enemy.stealth_wave_phase.sin() * StealthConfig::SHIMMER;
+    if enemy.charge_frames > 0.0 || enemy.ai_mode == AiMode::Telegraph {
+        enemy.visibility = (enemy.visibility + StealthConfig::DECLOAK_RATE).min(1.0);
+    } else {
+        enemy.visibility = (enemy.visibility - StealthConfig::RECLOAK_RATE).max(cloaked);
+    }
+}
+
+fn update_circle(index: usize, enemy: &mut EnemyState, archetype: &Archetype, flock: Option<&FlockSnapshot>) {
+    // Flockers with company mostly follow the flock; loners keep circling
+    let mut turn_rate = archetype.turn_rate;
+    if let (true, 0, Some(flock)) = (archetype.flock, enemy.squad, flock) {
+        let cfg = config::flock_config();
+        if flock::steer(index, enemy, flock, &cfg) {
+            turn_rate *= cfg.turn_scale;
+        }
+    }
+
+    let speed = enemy.speed(archetype);
+    let step = wander(&mut enemy.angle, turn_rate, archetype.wander_jitter, speed);
+    enemy.x += step.x;
+    enemy.y += step.y;
+}
+
+fn update_chase(
+    index: usize,
+    enemy: &mut EnemyState,
+    archetype: &Archetype,
+    ctx: &TickContext,
+    enemy_data: &[f64],
+) {
+    let hunts_ship = ctx.hunts_ship(enemy.faction);
+    let target = if hunts_ship {
+        (ctx.ship_x, ctx.ship_y)
+    } else {
+        find_nearest_hostile(index, enemy.faction, enemy.x, enemy.y, ctx, enemy_data)
+            .map_or((ctx.ship_x, ctx.ship_y), |(x, y, _)| (x, y))
+    };
+
+    let speed = enemy.speed(archetype) * aggression::effective(enemy);
+    let pos = Vec2::new(enemy.x, enemy.y);
+    let target = Vec2::new(target.0, target.1);
+    let path = if hunts_ship { ctx.path_to_ship(enemy.x, enemy.y) } else { None };
+    let dist = (target - pos).length();
+    let step = match path {
+        // Follow the flow field around obstacles, stopping at the same keep distance
+        Some(dir) if dist > archetype.keep_distance => dir * speed,
+        // Back off to the keep distance without overshooting it
+        _ if dist < archetype.keep_distance => flee(pos, target, speed.min(archetype.keep_distance - dist)),
+        _ => arrive(pos, target, speed, archetype.keep_distance, 0.0),
+    };
+    enemy.x += step.x;
+    enemy.y += step.y;
+}
+
 fn update_rammer(
+    index: usize,
     enemy: &mut EnemyState,
-    ship_x: f64,
-    ship_y: f64,
-    ship_radius: f64,
-    projectile_data: &[f64],
+    ctx: &TickContext,
     enemy_data: &[f64],
-    canvas_width: f64,
-    canvas_height: f64,
-    shield_active: bool,
+    events: &mut Vec<AiEvent>,
 ) {
+    let (ship_x, ship_y, ship_radius) = (ctx.ship_x, ctx.ship_y, ctx.ship_radius);
+    let projectile_data = ctx.projectile_data;
+    let cfg = rammer_config();
+    let aggr = aggression::effective(enemy);
+    let thrust = cfg.thrust * aggr;
+
     // Decrease cooldowns
     if enemy.hit_cooldown > 0.0 {
         enemy.hit_cooldown -= 1.0;
@@ -109,229 +244,388 @@ fn update_rammer(
         enemy.bounce_boost_frames -= 1.0;
     }
 
+    let pos = Vec2::new(enemy.x, enemy.y);
+    let ship = Vec2::new(ship_x, ship_y);
+    let mut vel = Vec2::new(enemy.vx, enemy.vy);
+    let dist_to_ship = (ship - pos).length();
+
+    let mode = next_rammer_mode(enemy, &cfg, dist_to_ship, aggr);
+    let charge_starting = mode == AiMode::Charge && enemy.ai_mode != AiMode::Charge;
+    transition(index, enemy, mode, events);
+
     // Forward vector
-    let mut fvx = enemy.vx;
-    let mut fvy = enemy.vy;
-    if fvx.abs() + fvy.abs() < 0.001 {
-        fvx = 1.0;
-        fvy = 0.0;
-    }
-    let fl = hypot(fvx, fvy).max(1.0);
-    let mut fx = fvx / fl;
-    let mut fy = fvy / fl;
+    let forward = if vel.x.abs() + vel.y.abs() < 0.001 { Vec2::new(1.0, 0.0) } else { vel }.soft_normalized();
+
+    let ship_vel = Vec2::new(ctx.ship_vx, ctx.ship_vy);
+    let speed = vel.length().max(cfg.base_max);
 
     // Target selection
-    let (target_x, target_y) = if enemy.is_zombie {
-        find_nearest_hostile(enemy.x, enemy.y, enemy_data)
-            .unwrap_or((enemy.x, enemy.y))
+    let target = if !ctx.hunts_ship(enemy.faction) {
+        find_nearest_hostile(index, enemy.faction, enemy.x, enemy.y, ctx, enemy_data)
+            .map_or(pos, |(x, y, _)| Vec2::new(x, y))
+    } else {
+        // Lead the ship: aim at the intercept point, scaled back toward the ship by PREDICT_STRENGTH
+        let intercept = intercept_point(pos, speed, ship, ship_vel, cfg.predict_max_frames);
+        ship + (intercept - ship) * cfg.predict_strength
+    };
+    let to_target = if mode == AiMode::Retreat {
+        (pos - ship).soft_normalized()
     } else {
-        // Predictive targeting
-        let predict_factor = 1.4;
-        (ship_x * predict_factor, ship_y * predict_factor)
+        (target - pos).soft_normalized()
     };
 
-    let mut dx = target_x - enemy.x;
-    let mut dy = target_y - enemy.y;
-    let dl = hypot(dx, dy).max(1.0);
-    dx /= dl;
-    dy /= dl;
-
-    let dist_to_ship = hypot(ship_x - enemy.x, ship_y - enemy.y);
-
-    // Dodge incoming projectiles
-    let num_proj = projectile_data.len() / 5;
-    for i in 0..num_proj {
-        let offset = i * 5;
-        let proj_type = projectile_data[offset + 4] as i32;
-        // Type 0=player, 1=module
-        if proj_type == 0 || proj_type == 1 {
-            let px = projectile_data[offset];
-            let py = projectile_data[offset + 1];
-            let pvx = projectile_data[offset + 2];
-            let pvy = projectile_data[offset + 3];
-
-            let pdx = px - enemy.x;
-            let pdy = py - enemy.y;
-            let pdist = hypot(pdx, pdy);
-
-            if pdist < RammerConfig::DODGE_DIST && dist_to_ship >= RammerConfig::ORBIT_BREAK_RADIUS {
-                let pvlen = hypot(pvx, pvy).max(1.0);
-                let pvx_norm = pvx / pvlen;
-                let pvy_norm = pvy / pvlen;
-                let approach = pdx * pvx_norm + pdy * pvy_norm;
-
-                if approach < 80.0 {
-                    let perp_x = -pvx_norm;
-                    let perp_y = -pvy_norm;
-                    enemy.vx += perp_x * RammerConfig::DODGE_FORCE;
-                    enemy.vy += perp_y * RammerConfig::DODGE_FORCE;
-                }
+    // Dodge incoming projectiles from factions hostile to this one, evading where they will be 10 frames out
+    if dist_to_ship >= cfg.orbit_break_radius {
+        let incoming = projectile_data
+            .chunks_exact(5)
+            .filter(|p| ctx.hostility.is_hostile(faction::from_f64(p[4]), enemy.faction))
+            .map(|p| (Vec2::new(p[0], p[1]), Vec2::new(p[2], p[3])));
+        vel += avoid_obstacles(pos, incoming, cfg.dodge_dist, 80.0, cfg.dodge_force * aggr, 10.0);
+    }
+
+    // Steering: blend current heading toward the target; a telegraphing rammer turns fully
+    let steer = if mode == AiMode::Telegraph { 1.0 } else { cfg.steer };
+    let mut dir = SteeringBlend::new()
+        .add(forward, 1.0 - steer)
+        .add(to_target, steer)
+        .result()
+        .soft_normalized();
+
+    match mode {
+        AiMode::Telegraph => vel += dir * (thrust * cfg.telegraph_thrust),
+        AiMode::Charge => {
+            if charge_starting {
+                enemy.charge_frames = cfg.charge_frames;
+                enemy.charge_cooldown = 220.0 + random() * 120.0;
+                vel += dir * cfg.charge_speed_bonus;
+                events.push(AiEvent::ChargeStarted { index, x: enemy.x, y: enemy.y, dir_x: dir.x, dir_y: dir.y });
             }
+            vel += dir * (thrust * 1.8);
+            enemy.bounce_boost_frames = enemy.bounce_boost_frames.max(cfg.charge_frames);
         }
+        _ => vel += dir * thrust,
     }
 
-    // Steering
-    let steer = RammerConfig::STEER;
-    let mut dirx = fx * (1.0 - steer) + dx * steer;
-    let mut diry = fy * (1.0 - steer) + dy * steer;
-    let dl2 = hypot(dirx, diry).max(1.0);
-    dirx /= dl2;
-    diry /= dl2;
-
-    // Charge behavior
-    if enemy.charge_frames > 0.0 {
-        enemy.vx += dirx * (RammerConfig::THRUST * 1.8);
-        enemy.vy += diry * (RammerConfig::THRUST * 1.8);
-        enemy.bounce_boost_frames = enemy.bounce_boost_frames.max(RammerConfig::CHARGE_FRAMES);
-    } else {
-        enemy.vx += dirx * RammerConfig::THRUST;
-        enemy.vy += diry * RammerConfig::THRUST;
-
-        if enemy.charge_cooldown <= 0.0 && dist_to_ship < RammerConfig::CHARGE_DIST && random() < RammerConfig::CHARGE_PROB {
-            enemy.charge_frames = RammerConfig::CHARGE_FRAMES;
-            enemy.charge_cooldown = 220.0 + random() * 120.0;
-            enemy.vx += dirx * RammerConfig::CHARGE_SPEED_BONUS;
-            enemy.vy += diry * RammerConfig::CHARGE_SPEED_BONUS;
-        }
+    // Anti-orbit: kill sideways drift near the ship and bias toward where it is heading
+    if dist_to_ship < cfg.orbit_break_radius && mode != AiMode::Retreat {
+        let n = pursue(pos, ship, ship_vel * cfg.predict_strength, speed, cfg.predict_max_frames).soft_normalized();
+        vel = damp_tangential(vel, n, cfg.orbit_tangent_damp) + n * 0.8;
+        dir = SteeringBlend::new()
+            .add(dir, 1.0 - cfg.close_steer)
+            .add(n, cfg.close_steer)
+            .result()
+            .soft_normalized();
     }
 
-    // Anti-orbit logic
-    if dist_to_ship < RammerConfig::ORBIT_BREAK_RADIUS {
-        let nx = (ship_x - enemy.x) / dist_to_ship.max(1.0);
-        let ny = (ship_y - enemy.y) / dist_to_ship.max(1.0);
-        let vdotn = enemy.vx * nx + enemy.vy * ny;
-        let vrx = nx * vdotn;
-        let vry = ny * vdotn;
-        let vtx = enemy.vx - vrx;
-        let vty = enemy.vy - vry;
-        enemy.vx = vrx + vtx * RammerConfig::ORBIT_TANGENT_DAMP;
-        enemy.vy = vry + vty * RammerConfig::ORBIT_TANGENT_DAMP;
-        enemy.vx += nx * 0.8;
-        enemy.vy += ny * 0.8;
-
-        dirx = dirx * (1.0 - RammerConfig::CLOSE_STEER) + nx * RammerConfig::CLOSE_STEER;
-        diry = diry * (1.0 - RammerConfig::CLOSE_STEER) + ny * RammerConfig::CLOSE_STEER;
-        let ndl = hypot(dirx, diry).max(1.0);
-        dirx /= ndl;
-        diry /= ndl;
-    }
-
-    // Velocity damping and clamping
-    enemy.vx *= RammerConfig::DAMP;
-    enemy.vy *= RammerConfig::DAMP;
-    let v = hypot(enemy.vx, enemy.vy);
+    // Velocity damping and truncation
     let max_v = if enemy.bounce_boost_frames > 0.0 || enemy.charge_frames > 0.0 {
-        RammerConfig::BOOST_MAX
+        cfg.boost_max
     } else {
-        RammerConfig::BASE_MAX
+        cfg.base_max
     };
-
-    if v > max_v {
-        enemy.vx = enemy.vx / v * max_v;
-        enemy.vy = enemy.vy / v * max_v;
-    }
+    vel = (vel * cfg.damp).truncate(max_v);
 
     // Ensure minimal forward speed when engaging
     if dist_to_ship < 220.0 {
-        let v2 = hypot(enemy.vx, enemy.vy);
-        if v2 < RammerConfig::MIN_FWD {
-            enemy.vx += dirx * (RammerConfig::MIN_FWD - v2);
-            enemy.vy += diry * (RammerConfig::MIN_FWD - v2);
+        let v2 = vel.length();
+        if v2 < cfg.min_fwd {
+            vel += dir * (cfg.min_fwd - v2);
         }
     }
 
     // Apply velocity
-    enemy.x += enemy.vx;
-    enemy.y += enemy.vy;
+    enemy.vx = vel.x;
+    enemy.vy = vel.y;
+    enemy.x += vel.x;
+    enemy.y += vel.y;
 
     // Boundary bounce with HP damage
-    handle_boundary_bounce(enemy, canvas_width, canvas_height);
+    let mut bounced = false;
+    if handle_boundary_bounce(enemy, &cfg, ctx.canvas_width, ctx.canvas_height) {
+        apply_self_damage(index, enemy, cfg.hit_damage, DamageCause::WallBounce, events);
+        bounced = true;
+    }
 
-    // Shield repel (not fully implemented in WASM - would require game state modification)
-    // Note: Complex interactions like shield repel are better handled in JS for now
+    // Shield repel
+    if ctx.shield_active && handle_shield_repel(enemy, &cfg, ship_x, ship_y, ship_radius) {
+        apply_self_damage(index, enemy, cfg.hit_damage, DamageCause::ShieldRepel, events);
+        bounced = true;
+    }
+
+    // Collision with ship
+    if !ctx.shield_active && enemy.hit_cooldown <= 0.0 {
+        match handle_ship_collision(index, enemy, &cfg, ship_x, ship_y, ship_radius, events) {
+            Some(true) if cfg.retreat_frames > 0.0 => transition(index, enemy, AiMode::Retreat, events),
+            Some(_) => bounced = true,
+            None => {}
+        }
+    }
 
-    // Note: Ship and enemy collisions are better handled in the main collision detection system
+    if bounced && matches!(enemy.ai_mode, AiMode::Cruise | AiMode::OrbitBreak) {
+        transition(index, enemy, AiMode::Rebound, events);
+    }
 }
 
 fn update_exploder(
+    index: usize,
     enemy: &mut EnemyState,
-    ship_x: f64,
-    ship_y: f64,
-    ship_radius: f64,
-    module_data: &[f64],
+    archetype: &Archetype,
+    ctx: &TickContext,
     enemy_data: &[f64],
-    canvas_width: f64,
-    canvas_height: f64,
-    shield_active: bool,
+    events: &mut Vec<AiEvent>,
 ) {
     enemy.pulse_phase += 0.1;
 
-    let (target_x, target_y) = if enemy.is_zombie {
-        match find_nearest_hostile(enemy.x, enemy.y, enemy_data) {
-            Some((x, y)) => (x, y),
+    let hunts_ship = ctx.hunts_ship(enemy.faction);
+    let (target_x, target_y) = if hunts_ship {
+        (ctx.ship_x, ctx.ship_y)
+    } else {
+        match find_nearest_hostile(index, enemy.faction, enemy.x, enemy.y, ctx, enemy_data) {
+            Some((x, y, _)) => (x, y),
             None => return, // No enemies to chase
         }
-    } else {
-        (ship_x, ship_y)
     };
 
-    let dx = target_x - enemy.x;
-    let dy = target_y - enemy.y;
-    let dist = hypot(dx, dy);
+    let path = if hunts_ship { ctx.path_to_ship(enemy.x, enemy.y) } else { None };
+    let step = match path {
+        Some(dir) => dir * enemy.speed(archetype),
+        None => seek(Vec2::new(enemy.x, enemy.y), Vec2::new(target_x, target_y), enemy.speed(archetype)),
+    };
+    enemy.x += step.x;
+    enemy.y += step.y;
+
+    if exploder_triggered(index, enemy, ctx, enemy_data) {
+        detonate(index, enemy, ctx, enemy_data, events);
+    }
+}
+
+// Exploders hunting the ship go off on contact with it or a module, others (zombies) on a hostile enemy
+fn exploder_triggered(index: usize, enemy: &EnemyState, ctx: &TickContext, enemy_data: &[f64]) -> bool {
+    if !ctx.hunts_ship(enemy.faction) {
+        let num_enemies = enemy_data.len() / ENEMY_STRIDE;
+        return (0..num_enemies).any(|i| {
+            let offset = i * ENEMY_STRIDE;
+            i != index
+                && enemy_data[offset + 8] > 0.0
+                && ctx.hostility.is_hostile(enemy.faction, faction::from_f64(enemy_data[offset + 5]))
+                && hypot(enemy.x - enemy_data[offset], enemy.y - enemy_data[offset + 1])
+                    < enemy.radius + enemy_data[offset + 7]
+        });
+    }
 
-    if dist > 0.0 {
-        let speed = enemy.radius * 0.0145; // speed=0.8 for radius=55
-        enemy.x += (dx / dist) * speed;
-        enemy.y += (dy / dist) * speed;
+    if hypot(enemy.x - ctx.ship_x, enemy.y - ctx.ship_y) < enemy.radius + ctx.ship_radius {
+        return true;
     }
 
-    // Note: Explosion logic on contact is better handled in main game loop
-    // as it requires particle creation and game state modification
+    ctx.module_data.chunks_exact(3).any(|module| {
+        hypot(enemy.x - module[0], enemy.y - module[1]) < enemy.radius + module[2]
+    })
 }
 
-fn handle_boundary_bounce(enemy: &mut EnemyState, canvas_width: f64, canvas_height: f64) {
+// Radial damage around the exploder; it dies in the blast
+fn detonate(
+    index: usize,
+    enemy: &mut EnemyState,
+    ctx: &TickContext,
+    enemy_data: &[f64],
+    events: &mut Vec<AiEvent>,
+) {
+    let blast_radius = enemy.radius * ExploderConfig::BLAST_RADIUS_SCALE;
+
+    events.push(AiEvent::Exploded {
+        index,
+        x: enemy.x,
+        y: enemy.y,
+        radius: blast_radius,
+        faction: enemy.faction,
+    });
+
+    if !ctx.hunts_ship(enemy.faction) {
+        // Zombie blast hurts every hostile enemy it reaches
+        let num_enemies = enemy_data.len() / ENEMY_STRIDE;
+        for i in 0..num_enemies {
+            let offset = i * ENEMY_STRIDE;
+            if i == index
+                || enemy_data[offset + 8] <= 0.0
+                || !ctx.hostility.is_hostile(enemy.faction, faction::from_f64(enemy_data[offset + 5]))
+            {
+                continue;
+            }
+            let dist = hypot(enemy.x - enemy_data[offset], enemy.y - enemy_data[offset + 1]);
+            if dist < blast_radius + enemy_data[offset + 7] {
+                events.push(AiEvent::AreaDamage {
+                    target: i,
+                    amount: ExploderConfig::ENEMY_DAMAGE,
+                    source: index,
+                });
+            }
+        }
+    } else if !ctx.shield_active {
+        // Ship and modules share the ship's energy pool, so the blast drains it once
+        let ship_in_blast = hypot(enemy.x - ctx.ship_x, enemy.y - ctx.ship_y) < blast_radius + ctx.ship_radius;
+        let module_in_blast = ctx.module_data.chunks_exact(3).any(|module| {
+            hypot(enemy.x - module[0], enemy.y - module[1]) < blast_radius + module[2]
+        });
+        if ship_in_blast || module_in_blast {
+            events.push(AiEvent::ShipDamage {
+                source: index,
+                amount: ExploderConfig::SHIP_DAMAGE,
+                cause: DamageCause::ExploderBlast,
+            });
+        }
+    }
+
+    enemy.hp = 0.0;
+}
+
+fn handle_boundary_bounce(enemy: &mut EnemyState, cfg: &RammerConfig, canvas_width: f64, canvas_height: f64) -> bool {
     let mut bounced = false;
 
     if enemy.x < enemy.radius {
         enemy.x = enemy.radius;
-        enemy.vx = -enemy.vx * RammerConfig::REST;
+        enemy.vx = -enemy.vx * cfg.rest;
         bounced = true;
     }
     if enemy.x > canvas_width - enemy.radius {
         enemy.x = canvas_width - enemy.radius;
-        enemy.vx = -enemy.vx * RammerConfig::REST;
+        enemy.vx = -enemy.vx * cfg.rest;
         bounced = true;
     }
     if enemy.y < enemy.radius {
         enemy.y = enemy.radius;
-        enemy.vy = -enemy.vy * RammerConfig::REST;
+        enemy.vy = -enemy.vy * cfg.rest;
         bounced = true;
     }
     if enemy.y > canvas_height - enemy.radius {
         enemy.y = canvas_height - enemy.radius;
-        enemy.vy = -enemy.vy * RammerConfig::REST;
+        enemy.vy = -enemy.vy * cfg.rest;
         bounced = true;
     }
 
     if bounced {
-        enemy.bounce_boost_frames = RammerConfig::BOOST_FRAMES;
-        enemy.hit_cooldown = RammerConfig::HIT_CD;
-        clamp_velocity(enemy);
-        // HP damage would be applied in JS
+        enemy.bounce_boost_frames = cfg.boost_frames;
+        enemy.hit_cooldown = cfg.hit_cd;
+        clamp_velocity(enemy, cfg);
+    }
+
+    bounced
+}
+
+// Push the rammer out of the shield and reflect its velocity; returns true on contact
+fn handle_shield_repel(
+    enemy: &mut EnemyState,
+    cfg: &RammerConfig,
+    ship_x: f64,
+    ship_y: f64,
+    ship_radius: f64,
+) -> bool {
+    let d_ship = hypot(enemy.x - ship_x, enemy.y - ship_y);
+    let shield_radius = ship_radius + cfg.shield_padding;
+    let repel_dist = shield_radius + enemy.radius;
+
+    if d_ship >= repel_dist {
+        return false;
+    }
+
+    let nx = (enemy.x - ship_x) / d_ship.max(1.0);
+    let ny = (enemy.y - ship_y) / d_ship.max(1.0);
+    enemy.x = ship_x + nx * repel_dist;
+    enemy.y = ship_y + ny * repel_dist;
+
+    let dot = enemy.vx * nx + enemy.vy * ny;
+    enemy.vx = (enemy.vx - 2.0 * dot * nx) * cfg.rest;
+    enemy.vy = (enemy.vy - 2.0 * dot * ny) * cfg.rest;
+    enemy.bounce_boost_frames = cfg.boost_frames;
+    enemy.hit_cooldown = cfg.hit_cd;
+    clamp_velocity(enemy, cfg);
+
+    true
+}
+
+// Tip hits (rammer nose aligned with the ship) drain ship energy; glancing hits hurt the rammer.
+// Returns None without contact, otherwise whether it was a tip hit.
+fn handle_ship_collision(
+    index: usize,
+    enemy: &mut EnemyState,
+    cfg: &RammerConfig,
+    ship_x: f64,
+    ship_y: f64,
+    ship_radius: f64,
+    events: &mut Vec<AiEvent>,
+) -> Option<bool> {
+    let d_ship = hypot(enemy.x - ship_x, enemy.y - ship_y);
+    if d_ship >= enemy.radius + ship_radius {
+        return None;
+    }
+
+    let mut fvx = enemy.vx;
+    let mut fvy = enemy.vy;
+    if fvx.abs() + fvy.abs() < 0.001 {
+        fvx = 1.0;
+        fvy = 0.0;
+    }
+    let fl = hypot(fvx, fvy).max(1.0);
+    let fx = fvx / fl;
+    let fy = fvy / fl;
+
+    let nx = (enemy.x - ship_x) / d_ship.max(1.0);
+    let ny = (enemy.y - ship_y) / d_ship.max(1.0);
+    let align = -(fx * nx + fy * ny);
+    let tip_hit = align > cfg.tip_align;
+
+    if tip_hit {
+        events.push(AiEvent::ShipDamage {
+            source: index,
+            amount: cfg.tip_damage,
+            cause: DamageCause::RammerTip,
+        });
+    }
+
+    let dot = enemy.vx * nx + enemy.vy * ny;
+    enemy.vx = (enemy.vx - 2.0 * dot * nx) * cfg.rest;
+    enemy.vy = (enemy.vy - 2.0 * dot * ny) * cfg.rest;
+    enemy.bounce_boost_frames = cfg.boost_frames;
+    enemy.hit_cooldown = cfg.hit_cd;
+    clamp_velocity(enemy, cfg);
+    enemy.x = ship_x + nx * (enemy.radius + ship_radius + 1.0);
+    enemy.y = ship_y + ny * (enemy.radius + ship_radius + 1.0);
+
+    if !tip_hit {
+        apply_self_damage(index, enemy, cfg.hit_damage, DamageCause::ShipCollision, events);
     }
+
+    Some(tip_hit)
+}
+
+fn apply_self_damage(
+    index: usize,
+    enemy: &mut EnemyState,
+    amount: f64,
+    cause: DamageCause,
+    events: &mut Vec<AiEvent>,
+) {
+    enemy.hp -= amount;
+    events.push(AiEvent::SelfDamage { index, amount, cause });
 }
 
-fn clamp_velocity(enemy: &mut EnemyState) {
+fn clamp_velocity(enemy: &mut EnemyState, cfg: &RammerConfig) {
     let v = hypot(enemy.vx, enemy.vy);
-    let cap = RammerConfig::BOOST_MAX;
+    let cap = cfg.boost_max;
     if v > cap {
         enemy.vx = enemy.vx / v * cap;
         enemy.vy = enemy.vy / v * cap;
     }
 }
 
-// Find nearest non-zombie enemy for zombie targeting
-fn find_nearest_hostile(x: f64, y: f64, enemy_data: &[f64]) -> Option<(f64, f64)> {
+// Nearest other enemy that `faction` is hostile to, with its squared distance
+fn find_nearest_hostile(
+    index: usize,
+    faction: FactionId,
+    x: f64,
+    y: f64,
+    ctx: &TickContext,
+    enemy_data: &[f64],
+) -> Option<(f64, f64, f64)> {
     let num_enemies = enemy_data.len() / ENEMY_STRIDE;
     let mut best_dist_sq = f64::INFINITY;
     let mut result = None;
@@ -340,19 +634,263 @@ fn find_nearest_hostile(x: f64, y: f64, enemy_data: &[f64]) -> Option<(f64, f64)
         let offset = i * ENEMY_STRIDE;
         let ex = enemy_data[offset];
         let ey = enemy_data[offset + 1];
-        let is_zombie = enemy_data[offset + 5] > 0.5;
 
-        if !is_zombie {
+        if i != index
+            && enemy_data[offset + 8] > 0.0
+            && ctx.hostility.is_hostile(faction, faction::from_f64(enemy_data[offset + 5]))
+        {
             let dx = ex - x;
             let dy = ey - y;
             let dist_sq = dx * dx + dy * dy;
 
             if dist_sq < best_dist_sq {
                 best_dist_sq = dist_sq;
-                result = Some((ex, ey));
+                result = Some((ex, ey, dist_sq));
             }
         }
     }
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archetype;
+
+    fn rammer(x: f64, y: f64, vx: f64, vy: f64) -> EnemyState {
+        let mut record = [0.0; ENEMY_STRIDE];
+        record[0] = x;
+        record[1] = y;
+        record[2] = vx;
+        record[3] = vy;
+        record[5] = faction::ENEMY as f64;
+        record[7] = 18.0;
+        record[8] = 50.0;
+        record[9] = 50.0;
+        EnemyState::from_slice(&record)
+    }
+
+    #[test]
+    fn shield_repel_pushes_out_and_reflects() {
+        let cfg = RammerConfig::default();
+        let mut enemy = rammer(450.0, 300.0, -5.0, 0.0);
+        assert!(handle_shield_repel(&mut enemy, &cfg, 400.0, 300.0, 20.0));
+
+        assert!((enemy.x - (400.0 + 20.0 + cfg.shield_padding + 18.0)).abs() < 1e-9);
+        assert_eq!(enemy.y, 300.0);
+        assert!((enemy.vx - 5.0 * cfg.rest).abs() < 1e-9);
+        assert_eq!(enemy.hit_cooldown, cfg.hit_cd);
+        assert_eq!(enemy.bounce_boost_frames, cfg.boost_frames);
+    }
+
+    #[test]
+    fn shielded_ship_repels_rammer_with_self_damage() {
+        archetype::with_archetypes(|archetypes| {
+            let ctx = TickContext { shield_active: true, ..TickContext::for_test(archetypes) };
+            let mut enemy = rammer(450.0, 300.0, -5.0, 0.0);
+            enemy.enemy_type = archetype::find("rammer").unwrap();
+            let mut record = [0.0; ENEMY_STRIDE];
+            enemy.write_to_slice(&mut record);
+
+            let mut events = Vec::new();
+            let enemy = update_single_enemy(0, enemy, &ctx, &record, None, &SquadPlan::default(), &mut events);
+            assert!(hypot(enemy.x - 400.0, enemy.y - 300.0) >= 20.0 + RammerConfig::default().shield_padding + 18.0 - 1e-9);
+            assert!(events.iter().any(|event| matches!(
+                event,
+                AiEvent::SelfDamage { index: 0, cause: DamageCause::ShieldRepel, .. }
+            )));
+        });
+    }
+
+    #[test]
+    fn shield_repel_ignores_rammers_outside_the_shield() {
+        let cfg = RammerConfig::default();
+        let mut enemy = rammer(500.0, 300.0, -5.0, 0.0);
+        assert!(!handle_shield_repel(&mut enemy, &cfg, 400.0, 300.0, 20.0));
+        assert_eq!((enemy.x, enemy.vx), (500.0, -5.0));
+    }
+
+    #[test]
+    fn nose_first_contact_is_a_tip_hit() {
+        let cfg = RammerConfig::default();
+        let mut enemy = rammer(430.0, 300.0, -8.0, 0.0);
+        let mut events = Vec::new();
+        assert_eq!(handle_ship_collision(3, &mut enemy, &cfg, 400.0, 300.0, 20.0, &mut events), Some(true));
+        assert_eq!(
+            events,
+            vec![AiEvent::ShipDamage { source: 3, amount: cfg.tip_damage, cause: DamageCause::RammerTip }]
+        );
+        assert_eq!(enemy.hp, 50.0);
+        assert!(enemy.vx > 0.0);
+    }
+
+    #[test]
+    fn glancing_contact_damages_the_rammer() {
+        let cfg = RammerConfig::default();
+        let mut enemy = rammer(430.0, 300.0, 0.0, 8.0);
+        let mut events = Vec::new();
+        assert_eq!(handle_ship_collision(3, &mut enemy, &cfg, 400.0, 300.0, 20.0, &mut events), Some(false));
+        assert_eq!(
+            events,
+            vec![AiEvent::SelfDamage { index: 3, amount: cfg.hit_damage, cause: DamageCause::ShipCollision }]
+        );
+        assert_eq!(enemy.hp, 50.0 - cfg.hit_damage);
+        assert!(hypot(enemy.x - 400.0, enemy.y - 300.0) > 18.0 + 20.0);
+    }
+
+    // Radius 15 records from (x, y, faction, hp)
+    fn buffer(records: &[(f64, f64, FactionId, f64)]) -> Vec<f64> {
+        let mut data = Vec::new();
+        for &(x, y, owner, hp) in records {
+            let mut record = [0.0; ENEMY_STRIDE];
+            record[0] = x;
+            record[1] = y;
+            record[5] = owner as f64;
+            record[7] = 15.0;
+            record[8] = hp;
+            data.extend_from_slice(&record);
+        }
+        data
+    }
+
+    fn exploder(x: f64, y: f64, owner: FactionId) -> EnemyState {
+        let mut enemy = rammer(x, y, 0.0, 0.0);
+        enemy.faction = owner;
+        enemy.radius = 15.0;
+        enemy.zombie_lifetime = 600.0;
+        enemy
+    }
+
+    #[test]
+    fn zombie_exploder_triggers_only_on_living_hostiles() {
+        let live = buffer(&[(300.0, 300.0, faction::ZOMBIE, 10.0), (320.0, 300.0, faction::ENEMY, 10.0)]);
+        let dead = buffer(&[(300.0, 300.0, faction::ZOMBIE, 10.0), (320.0, 300.0, faction::ENEMY, 0.0)]);
+        archetype::with_archetypes(|archetypes| {
+            let ctx = TickContext::for_test(archetypes);
+            let enemy = exploder(300.0, 300.0, faction::ZOMBIE);
+            assert!(exploder_triggered(0, &enemy, &ctx, &live));
+            assert!(!exploder_triggered(0, &enemy, &ctx, &dead));
+        });
+    }
+
+    #[test]
+    fn zombie_blast_damages_living_hostiles_in_range() {
+        let enemy_data = buffer(&[
+            (300.0, 300.0, faction::ZOMBIE, 10.0),
+            (330.0, 300.0, faction::ENEMY, 10.0),
+            (300.0, 330.0, faction::ENEMY, 0.0),
+            (310.0, 310.0, faction::ZOMBIE, 10.0),
+            (700.0, 500.0, faction::ENEMY, 10.0),
+        ]);
+        archetype::with_archetypes(|archetypes| {
+            let ctx = TickContext::for_test(archetypes);
+            let mut enemy = exploder(300.0, 300.0, faction::ZOMBIE);
+            let mut events = Vec::new();
+            detonate(0, &mut enemy, &ctx, &enemy_data, &mut events);
+
+            assert_eq!(enemy.hp, 0.0);
+            assert!(matches!(events[0], AiEvent::Exploded { index: 0, .. }));
+            assert_eq!(
+                &events[1..],
+                &[AiEvent::AreaDamage { target: 1, amount: ExploderConfig::ENEMY_DAMAGE, source: 0 }]
+            );
+        });
+    }
+
+    #[test]
+    fn blast_on_the_ship_is_stopped_by_the_shield() {
+        archetype::with_archetypes(|archetypes| {
+            for shield_active in [false, true] {
+                let ctx = TickContext { shield_active, ..TickContext::for_test(archetypes) };
+                let mut enemy = exploder(420.0, 300.0, faction::ENEMY);
+                let mut events = Vec::new();
+                detonate(0, &mut enemy, &ctx, &[], &mut events);
+                let ship_hit = events.iter().any(|event| {
+                    matches!(event, AiEvent::ShipDamage { cause: DamageCause::ExploderBlast, .. })
+                });
+                assert_eq!(ship_hit, !shield_active);
+            }
+        });
+    }
+
+    #[test]
+    fn dead_enemies_do_not_act() {
+        archetype::with_archetypes(|archetypes| {
+            let ctx = TickContext::for_test(archetypes);
+            let mut enemy = rammer(430.0, 300.0, -8.0, 0.0);
+            enemy.enemy_type = archetype::find("rammer").unwrap();
+            enemy.hp = 0.0;
+            let mut events = Vec::new();
+            let after = update_single_enemy(0, enemy.clone(), &ctx, &[], None, &SquadPlan::default(), &mut events);
+            assert!(events.is_empty());
+            assert_eq!((after.x, after.y, after.vx), (enemy.x, enemy.y, enemy.vx));
+        });
+    }
+
+    #[test]
+    fn stealth_decloaks_while_charging_and_recloaks_after() {
+        let mut enemy = rammer(300.0, 300.0, 0.0, 0.0);
+        enemy.is_stealth = true;
+        enemy.visibility = StealthConfig::CLOAKED_VISIBILITY;
+        enemy.charge_frames = 10.0;
+        for _ in 0..10 {
+            update_stealth(&mut enemy);
+        }
+        assert_eq!(enemy.visibility, 1.0);
+
+        enemy.charge_frames = 0.0;
+        for _ in 0..100 {
+            update_stealth(&mut enemy);
+        }
+        assert_eq!(enemy.visibility, StealthConfig::CLOAKED_VISIBILITY);
+    }
+
+    #[test]
+    fn visible_enemies_stay_fully_visible() {
+        let mut enemy = rammer(300.0, 300.0, 0.0, 0.0);
+        enemy.visibility = 0.3;
+        update_stealth(&mut enemy);
+        assert_eq!(enemy.visibility, 1.0);
+    }
+
+    #[test]
+    fn elite_backs_off_to_its_keep_distance() {
+        archetype::with_archetypes(|archetypes| {
+            let ctx = TickContext::for_test(archetypes);
+            let elite = &archetypes[archetype::find("elite").unwrap()];
+            let mut enemy = rammer(400.0 + elite.keep_distance - 0.5, 300.0, 0.0, 0.0);
+            enemy.aggression = 1.0;
+            update_chase(0, &mut enemy, elite, &ctx, &[]);
+            assert!((enemy.x - (400.0 + elite.keep_distance)).abs() < 1e-9);
+
+            let mut enemy = rammer(450.0, 300.0, 0.0, 0.0);
+            enemy.aggression = 1.0;
+            update_chase(0, &mut enemy, elite, &ctx, &[]);
+            assert!(enemy.x > 450.0 && enemy.y == 300.0);
+        });
+    }
+
+    #[test]
+    fn rammer_leads_a_moving_ship() {
+        archetype::with_archetypes(|archetypes| {
+            // Out of charge range so the first tick is a plain cruise
+            let steer = |ship_vy: f64| {
+                let ctx = TickContext { ship_vy, ..TickContext::for_test(archetypes) };
+                let mut enemy = rammer(40.0, 300.0, 4.0, 0.0);
+                enemy.aggression = 1.0;
+                let mut events = Vec::new();
+                update_rammer(0, &mut enemy, &ctx, &[], &mut events);
+                assert!(events.is_empty());
+                (enemy.vx, enemy.vy)
+            };
+
+            let (_, still) = steer(0.0);
+            let (_, down) = steer(5.0);
+            let (_, up) = steer(-5.0);
+            assert!(still.abs() < 1e-9);
+            assert!(down > 0.1);
+            assert!((up + down).abs() < 1e-9);
+        });
+    }
+}
@@ -0,0 +1,190 @@
+// AI event stream - side effects collected during a tick for the host to apply
+//
+// Events are flattened into fixed-size f64 records:
+//   [kind, index, a, b, c, d, e, f]
+//
+//   SHOT            index=shooter  a=x b=y c=vx d=vy e=damage f=faction (shooter's)
+//   SELF_DAMAGE     index=enemy    a=amount b=cause
+//   SHIP_DAMAGE     index=source   a=amount b=cause
+//   ZOMBIE_EXPIRED  index=enemy    a=x b=y
+//   CHARGE_STARTED  index=enemy    a=x b=y c=dir_x d=dir_y
+//   KILLED          index=enemy    a=x b=y c=faction
+//   EXPLODED        index=enemy    a=x b=y c=blast_radius d=faction
+//   AREA_DAMAGE     index=target   a=amount b=source
+//   STATE_CHANGED   index=enemy    a=from b=to c=x d=y (AiMode ids, see fsm.rs)
+//   SQUAD_LEADER    index=enemy    a=squad b=members (promoted to lead its squad)
+
+pub const AI_EVENT_STRIDE: usize = 8;
+
+pub const EVENT_SHOT: f64 = 0.0;
+pub const EVENT_SELF_DAMAGE: f64 = 1.0;
+pub const EVENT_SHIP_DAMAGE: f64 = 2.0;
+pub const EVENT_ZOMBIE_EXPIRED: f64 = 3.0;
+pub const EVENT_CHARGE_STARTED: f64 = 4.0;
+pub const EVENT_KILLED: f64 = 5.0;
+pub const EVENT_EXPLODED: f64 = 6.0;
+pub const EVENT_AREA_DAMAGE: f64 = 7.0;
+pub const EVENT_STATE_CHANGED: f64 = 8.0;
+pub const EVENT_SQUAD_LEADER: f64 = 9.0;
+
+use crate::fsm::AiMode;
+use faction::FactionId;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DamageCause {
+    WallBounce = 0,
+    ShieldRepel = 1,
+    ShipCollision = 2,
+    RammerTip = 3,
+    ExploderBlast = 4,
+    EnemyCollision = 5,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AiEvent {
+    Shot {
+        source: usize,
+        x: f64,
+        y: f64,
+        vx: f64,
+        vy: f64,
+        damage: f64,
+        faction: FactionId,
+    },
+    SelfDamage {
+        index: usize,
+        amount: f64,
+        cause: DamageCause,
+    },
+    ShipDamage {
+        source: usize,
+        amount: f64,
+        cause: DamageCause,
+    },
+    ZombieExpired {
+        index: usize,
+        x: f64,
+        y: f64,
+    },
+    ChargeStarted {
+        index: usize,
+        x: f64,
+        y: f64,
+        dir_x: f64,
+        dir_y: f64,
+    },
+    Killed {
+        index: usize,
+        x: f64,
+        y: f64,
+        faction: FactionId,
+    },
+    Exploded {
+        index: usize,
+        x: f64,
+        y: f64,
+        radius: f64,
+        faction: FactionId,
+    },
+    // Damage dealt to another enemy; applied to the buffer by the tick loop
+    AreaDamage {
+        target: usize,
+        amount: f64,
+        source: usize,
+    },
+    StateChanged {
+        index: usize,
+        from: AiMode,
+        to: AiMode,
+        x: f64,
+        y: f64,
+    },
+    SquadLeader {
+        index: usize,
+        squad: u32,
+        members: usize,
+    },
+}
+
+impl AiEvent {
+    pub fn to_record(&self) -> [f64; AI_EVENT_STRIDE] {
+        match *self {
+            AiEvent::Shot { source, x, y, vx, vy, damage, faction } => {
+                [EVENT_SHOT, source as f64, x, y, vx, vy, damage, faction as f64]
+            }
+            AiEvent::SelfDamage { index, amount, cause } => {
+                [EVENT_SELF_DAMAGE, index as f64, amount, cause as i32 as f64, 0.0, 0.0, 0.0, 0.0]
+            }
+            AiEvent::ShipDamage { source, amount, cause } => {
+                [EVENT_SHIP_DAMAGE, source as f64, amount, cause as i32 as f64, 0.0, 0.0, 0.0, 0.0]
+            }
+            AiEvent::ZombieExpired { index, x, y } => {
+                [EVENT_ZOMBIE_EXPIRED, index as f64, x, y, 0.0, 0.0, 0.0, 0.0]
+            }
+            AiEvent::ChargeStarted { index, x, y, dir_x, dir_y } => {
+                [EVENT_CHARGE_STARTED, index as f64, x, y, dir_x, dir_y, 0.0, 0.0]
+            }
+            AiEvent::Killed { index, x, y, faction } => {
+                [EVENT_KILLED, index as f64, x, y, faction as f64, 0.0, 0.0, 0.0]
+            }
+            AiEvent::Exploded { index, x, y, radius, faction } => {
+                [EVENT_EXPLODED, index as f64, x, y, radius, faction as f64, 0.0, 0.0]
+            }
+            AiEvent::AreaDamage { target, amount, source } => {
+                [EVENT_AREA_DAMAGE, target as f64, amount, source as f64, 0.0, 0.0, 0.0, 0.0]
+            }
+            AiEvent::StateChanged { index, from, to, x, y } => {
+                [EVENT_STATE_CHANGED, index as f64, from as i32 as f64, to as i32 as f64, x, y, 0.0, 0.0]
+            }
+            AiEvent::SquadLeader { index, squad, members } => {
+                [EVENT_SQUAD_LEADER, index as f64, squad as f64, members as f64, 0.0, 0.0, 0.0, 0.0]
+            }
+        }
+    }
+}
+
+// Flatten events into the buffer layout documented above
+pub fn write_events(events: &[AiEvent], buffer: &mut Vec<f64>) {
+    buffer.clear();
+    buffer.reserve(events.len() * AI_EVENT_STRIDE);
+    for event in events {
+        buffer.extend_from_slice(&event.to_record());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_flatten_to_fixed_stride_records() {
+        let events = [
+            AiEvent::Shot { source: 2, x: 10.0, y: 20.0, vx: 1.5, vy: -2.5, damage: 7.0, faction: faction::ZOMBIE },
+            AiEvent::ShipDamage { source: 4, amount: 10.0, cause: DamageCause::RammerTip },
+            AiEvent::StateChanged { index: 1, from: AiMode::Telegraph, to: AiMode::Charge, x: 3.0, y: 4.0 },
+        ];
+        let mut buffer = vec![99.0; 3];
+        write_events(&events, &mut buffer);
+
+        assert_eq!(buffer.len(), events.len() * AI_EVENT_STRIDE);
+        assert_eq!(&buffer[..AI_EVENT_STRIDE], &[EVENT_SHOT, 2.0, 10.0, 20.0, 1.5, -2.5, 7.0, faction::ZOMBIE as f64]);
+        assert_eq!(&buffer[AI_EVENT_STRIDE..2 * AI_EVENT_STRIDE], &[EVENT_SHIP_DAMAGE, 4.0, 10.0, 3.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(&buffer[2 * AI_EVENT_STRIDE..], &[EVENT_STATE_CHANGED, 1.0, 1.0, 2.0, 3.0, 4.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn damage_causes_keep_their_ids() {
+        let causes = [
+            DamageCause::WallBounce,
+            DamageCause::ShieldRepel,
+            DamageCause::ShipCollision,
+            DamageCause::RammerTip,
+            DamageCause::ExploderBlast,
+            DamageCause::EnemyCollision,
+        ];
+        for (id, cause) in causes.into_iter().enumerate() {
+            let record = AiEvent::SelfDamage { index: 0, amount: 1.0, cause }.to_record();
+            assert_eq!(record[3], id as f64);
+        }
+    }
+}
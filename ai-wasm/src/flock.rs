@@ -0,0 +1,250 @@
+// Boids flocking for archetypes with "flock": true (circle movers outside a squad)
+// Each flocker looks at nearby flockers of its own faction in a start-of-tick FlockSnapshot
+// and turns its heading towards a blend of alignment (match their headings), cohesion (head for
+// their centre) and separation (keep clear of the closest ones), at most MAX_TURN per frame.
+use serde::{Serialize, Deserialize};
+
+use crate::grid::EnemyGrid;
+use crate::steering::*;
+use crate::types::*;
+use faction::FactionId;
+
+// Runtime tunable (see config.rs)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct FlockConfig {
+    // Flockers closer than this count as neighbours
+    pub neighbor_radius: f64,
+    // Neighbours closer than this push apart
+    pub separation_radius: f64,
+    pub alignment: f64,
+    pub cohesion: f64,
+    pub separation: f64,
+    // Largest heading change per frame (radians)
+    pub max_turn: f64,
+    // Share of the archetype's turn_rate kept while in a flock (0 = fly straight, 1 = keep circling)
+    pub turn_scale: f64,
+}
+
+impl Default for FlockConfig {
+    fn default() -> Self {
+        Self {
+            neighbor_radius: 120.0,
+            separation_radius: 45.0,
+            alignment: 1.0,
+            cohesion: 0.6,
+            separation: 1.8,
+            max_turn: 0.08,
+            turn_scale: 0.25,
+        }
+    }
+}
+
+impl FlockConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        fn check(name: &str, value: f64, min: f64, max: f64) -> Result<(), String> {
+            if value.is_finite() && value >= min && value <= max {
+                Ok(())
+            } else {
+                Err(format!("FlockConfig.{} = {} is outside [{}, {}]", name, value, min, max))
+            }
+        }
+
+        check("NEIGHBOR_RADIUS", self.neighbor_radius, 0.0, 1000.0)?;
+        check("SEPARATION_RADIUS", self.separation_radius, 0.0, self.neighbor_radius)?;
+        check("ALIGNMENT", self.alignment, 0.0, 10.0)?;
+        check("COHESION", self.cohesion, 0.0, 10.0)?;
+        check("SEPARATION", self.separation, 0.0, 10.0)?;
+        check("MAX_TURN", self.max_turn, 0.0, std::f64::consts::PI)?;
+        check("TURN_SCALE", self.turn_scale, 0.0, 1.0)?;
+        Ok(())
+    }
+
+    // Neighbour queries look at a 3x3 block, so cells must be at least neighbor_radius wide
+    pub fn cell_size(&self) -> f64 {
+        self.neighbor_radius.max(FLOCK_MIN_CELL)
+    }
+}
+
+const FLOCK_MIN_CELL: f64 = 128.0;
+
+// Flockers as they stood when the tick began. Neighbours are read from here rather than the
+// live buffer, so a flocker sees the same flock whether its neighbours updated before it or not.
+// One snapshot is rebuilt every tick, so its buffers are reused rather than reallocated.
+pub struct FlockSnapshot {
+    grid: EnemyGrid,
+    positions: Vec<f64>,
+    // None for enemies that do not flock this tick (dead, in a squad, or a non-flock archetype)
+    boids: Vec<Option<Boid>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Boid {
+    pos: Vec2,
+    heading: f64,
+    faction: FactionId,
+}
+
+impl Default for FlockSnapshot {
+    fn default() -> Self {
+        Self {
+            grid: EnemyGrid::new(FLOCK_MIN_CELL),
+            positions: Vec::new(),
+            boids: Vec::new(),
+        }
+    }
+}
+
+impl FlockSnapshot {
+    // Refill from the tick's starting positions and headings; false when no loaded archetype flocks
+    pub fn rebuild(&mut self, enemy_data: &[f64], ctx: &TickContext, cfg: &FlockConfig) -> bool {
+        self.positions.clear();
+        self.boids.clear();
+        if !ctx.archetypes.iter().any(|a| a.flock) {
+            return false;
+        }
+
+        for record in enemy_data.chunks_exact(ENEMY_STRIDE) {
+            self.positions.extend_from_slice(&[record[0], record[1]]);
+            let flocks = record[8] > 0.0 && record[24] < 1.0 && ctx.archetype(record[4].max(0.0) as usize).flock;
+            self.boids.push(flocks.then(|| Boid {
+                pos: Vec2::new(record[0], record[1]),
+                heading: record[11],
+                faction: faction::from_f64(record[5]),
+            }));
+        }
+        self.grid.set_cell_size(cfg.cell_size());
+        self.grid.rebuild(&self.positions);
+        true
+    }
+
+    // Same-faction flockers other than `index` within `radius` of `pos`
+    fn neighbors(&self, index: usize, faction: FactionId, pos: Vec2, radius: f64) -> impl Iterator<Item = Boid> + '_ {
+        let radius_sq = radius * radius;
+        self.grid
+            .neighbors(pos.x, pos.y)
+            .filter(move |&j| j != index)
+            .filter_map(|j| self.boids[j])
+            .filter(move |other| other.faction == faction && (other.pos - pos).dot(other.pos - pos) <= radius_sq)
+    }
+}
+
+// Turn `enemy` towards its flock. Returns false when it has no neighbours, so the caller
+// can keep the archetype's plain wander.
+pub fn steer(index: usize, enemy: &mut EnemyState, flock: &FlockSnapshot, cfg: &FlockConfig) -> bool {
+    let pos = Vec2::new(enemy.x, enemy.y);
+    let mut heading_sum = Vec2::ZERO;
+    let mut center = Vec2::ZERO;
+    let mut count = 0usize;
+
+    for other in flock.neighbors(index, enemy.faction, pos, cfg.neighbor_radius) {
+        heading_sum += Vec2::from_angle(other.heading);
+        center += other.pos;
+        count += 1;
+    }
+
+    if count == 0 {
+        return false;
+    }
+    let close = flock.neighbors(index, enemy.faction, pos, cfg.separation_radius).map(|other| other.pos);
+
+    let center = center * (1.0 / count as f64);
+    let desired = SteeringBlend::new()
+        .add(Vec2::from_angle(enemy.angle), 1.0)
+        .add(heading_sum.normalized(), cfg.alignment)
+        .add((center - pos).normalized(), cfg.cohesion)
+        .add(separation(pos, close, cfg.separation_radius, 1.0), cfg.separation)
+        .result();
+    if desired == Vec2::ZERO {
+        return true;
+    }
+
+    let target = desired.y.atan2(desired.x);
+    let mut delta = (target - enemy.angle) % TWO_PI;
+    if delta > std::f64::consts::PI {
+        delta -= TWO_PI;
+    } else if delta < -std::f64::consts::PI {
+        delta += TWO_PI;
+    }
+    enemy.angle += clamp(delta, -cfg.max_turn, cfg.max_turn);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archetype;
+
+    // Basic (flocking) enemies: (x, y, heading)
+    fn flock_buffer(boids: &[(f64, f64, f64)]) -> Vec<f64> {
+        let mut data = Vec::new();
+        for &(x, y, heading) in boids {
+            let mut record = [0.0; ENEMY_STRIDE];
+            record[0] = x;
+            record[1] = y;
+            record[5] = faction::ENEMY as f64;
+            record[7] = 15.0;
+            record[8] = 50.0;
+            record[11] = heading;
+            data.extend_from_slice(&record);
+        }
+        data
+    }
+
+    // Steer and move every flocker in `order`, writing each back before the next one runs
+    fn steer_in_order(enemy_data: &[f64], flock: &FlockSnapshot, order: &[usize]) -> Vec<f64> {
+        let cfg = FlockConfig::default();
+        let mut data = enemy_data.to_vec();
+        for &i in order {
+            let record = &mut data[i * ENEMY_STRIDE..(i + 1) * ENEMY_STRIDE];
+            let mut enemy = EnemyState::from_slice(record);
+            assert!(steer(i, &mut enemy, flock, &cfg));
+            enemy.x += enemy.angle.cos() * 40.0;
+            enemy.y += enemy.angle.sin() * 40.0;
+            enemy.write_to_slice(record);
+        }
+        data.chunks_exact(ENEMY_STRIDE).map(|record| record[11]).collect()
+    }
+
+    #[test]
+    fn neighbours_come_from_the_snapshot_not_the_live_buffer() {
+        let enemy_data = flock_buffer(&[(300.0, 300.0, 0.0), (340.0, 310.0, 1.2), (320.0, 350.0, -2.0)]);
+        archetype::with_archetypes(|archetypes| {
+            let ctx = TickContext::for_test(archetypes);
+            let mut flock = FlockSnapshot::default();
+            assert!(flock.rebuild(&enemy_data, &ctx, &FlockConfig::default()));
+            let forward = steer_in_order(&enemy_data, &flock, &[0, 1, 2]);
+            let backward = steer_in_order(&enemy_data, &flock, &[2, 1, 0]);
+            assert_eq!(forward, backward);
+        });
+    }
+
+    #[test]
+    fn squad_members_and_other_factions_are_not_neighbours() {
+        let mut enemy_data = flock_buffer(&[(300.0, 300.0, 0.0), (340.0, 300.0, 1.0), (300.0, 340.0, 1.0)]);
+        enemy_data[ENEMY_STRIDE + 24] = 1.0;
+        enemy_data[2 * ENEMY_STRIDE + 5] = faction::ZOMBIE as f64;
+        archetype::with_archetypes(|archetypes| {
+            let ctx = TickContext::for_test(archetypes);
+            let mut flock = FlockSnapshot::default();
+            assert!(flock.rebuild(&enemy_data, &ctx, &FlockConfig::default()));
+            let mut enemy = EnemyState::from_slice(&enemy_data[..ENEMY_STRIDE]);
+            assert!(!steer(0, &mut enemy, &flock, &FlockConfig::default()));
+        });
+    }
+
+    #[test]
+    fn rebuild_replaces_the_previous_tick() {
+        let crowd = flock_buffer(&[(300.0, 300.0, 0.0), (340.0, 300.0, 1.0), (300.0, 340.0, 1.0)]);
+        let alone = flock_buffer(&[(300.0, 300.0, 0.0)]);
+        archetype::with_archetypes(|archetypes| {
+            let ctx = TickContext::for_test(archetypes);
+            let cfg = FlockConfig::default();
+            let mut flock = FlockSnapshot::default();
+            assert!(flock.rebuild(&crowd, &ctx, &cfg));
+            assert!(flock.rebuild(&alone, &ctx, &cfg));
+            let mut enemy = EnemyState::from_slice(&alone);
+            assert!(!steer(0, &mut enemy, &flock, &cfg));
+        });
+    }
+}
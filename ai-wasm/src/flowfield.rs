@@ -0,0 +1,426 @@
+// Flow field toward the ship - a Dijkstra map over a coarse grid of the arena
+// The host marks rectangles as blocked or gives them a traversal cost (1 = open ground,
+// higher = hazard to avoid). The map is rebuilt only when the ship enters another cell or
+// the regions / canvas change; ship-hunting chasers and exploders sample it for their
+// heading instead of stepping straight at the ship. With no regions the straight line is
+// already the shortest path, so the field is skipped and movement is unchanged.
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::steering::Vec2;
+
+pub const DEFAULT_CELL_SIZE: f64 = 40.0;
+pub const MIN_CELL_SIZE: f64 = 8.0;
+pub const MAX_CELL_SIZE: f64 = 512.0;
+pub const MAX_COST: f64 = 1000.0;
+
+// 8-connected neighbours; diagonals may not cut the corner of a blocked cell
+const NEIGHBORS: [(i32, i32); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RegionKind {
+    Blocked,
+    Cost(f64),
+}
+
+// World-space rectangle; later regions override earlier ones where they overlap
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Region {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+    kind: RegionKind,
+}
+
+// Open-set entry ordered so BinaryHeap pops the lowest distance first
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Frontier {
+    dist: f64,
+    cell: usize,
+}
+
+impl Eq for Frontier {}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.dist.total_cmp(&self.dist).then(other.cell.cmp(&self.cell))
+    }
+}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FlowField {
+    cell_size: f64,
+    cols: usize,
+    rows: usize,
+    regions: Vec<Region>,
+    // Rasterised regions: per-cell cost, INFINITY for blocked
+    cost: Vec<f64>,
+    // Path cost from each cell to the goal cell, INFINITY where unreachable
+    dist: Vec<f64>,
+    // Unit direction towards the cheapest neighbour
+    dirs: Vec<Vec2>,
+    goal: Option<usize>,
+    goal_pos: Vec2,
+    // Regions or canvas changed since the last rasterise
+    dirty: bool,
+}
+
+impl Default for FlowField {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE)
+    }
+}
+
+impl FlowField {
+    pub fn new(cell_size: f64) -> Self {
+        Self {
+            cell_size,
+            cols: 0,
+            rows: 0,
+            regions: Vec::new(),
+            cost: Vec::new(),
+            dist: Vec::new(),
+            dirs: Vec::new(),
+            goal: None,
+            goal_pos: Vec2::ZERO,
+            dirty: true,
+        }
+    }
+
+    // Anything to steer around?
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.regions.iter().any(|r| r.kind != RegionKind::Cost(1.0))
+    }
+
+    pub fn add_blocked(&mut self, x: f64, y: f64, w: f64, h: f64) -> Result<(), String> {
+        self.add_region(Region { x, y, w, h, kind: RegionKind::Blocked })
+    }
+
+    pub fn add_cost(&mut self, x: f64, y: f64, w: f64, h: f64, cost: f64) -> Result<(), String> {
+        if !(cost.is_finite() && (1.0..=MAX_COST).contains(&cost)) {
+            return Err(format!("flow field cost {} is outside [1, {}]", cost, MAX_COST));
+        }
+        self.add_region(Region { x, y, w, h, kind: RegionKind::Cost(cost) })
+    }
+
+    // Rejects NaN / infinite coordinates and negative sizes before they reach rasterise
+    fn add_region(&mut self, region: Region) -> Result<(), String> {
+        if ![region.x, region.y, region.w, region.h].iter().all(|v| v.is_finite()) {
+            return Err(format!(
+                "flow field region ({}, {}, {}, {}) is not finite",
+                region.x, region.y, region.w, region.h
+            ));
+        }
+        if region.w < 0.0 || region.h < 0.0 {
+            return Err(format!("flow field region size {} x {} is negative", region.w, region.h));
+        }
+        self.regions.push(region);
+        self.dirty = true;
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.regions.clear();
+        self.dirty = true;
+    }
+
+    #[inline]
+    fn cell_at(&self, x: f64, y: f64) -> Option<usize> {
+        let cx = (x / self.cell_size).floor();
+        let cy = (y / self.cell_size).floor();
+        if cx < 0.0 || cy < 0.0 || cx >= self.cols as f64 || cy >= self.rows as f64 {
+            return None;
+        }
+        Some(cy as usize * self.cols + cx as usize)
+    }
+
+    #[inline]
+    fn center(&self, cell: usize) -> Vec2 {
+        Vec2::new(
+            ((cell % self.cols) as f64 + 0.5) * self.cell_size,
+            ((cell / self.cols) as f64 + 0.5) * self.cell_size,
+        )
+    }
+
+    #[inline]
+    fn neighbor(&self, cell: usize, (dx, dy): (i32, i32)) -> Option<usize> {
+        let x = (cell % self.cols) as i32 + dx;
+        let y = (cell / self.cols) as i32 + dy;
+        if x < 0 || y < 0 || x >= self.cols as i32 || y >= self.rows as i32 {
+            return None;
+        }
+        Some(y as usize * self.cols + x as usize)
+    }
+
+    #[inline]
+    fn blocked(&self, cell: usize) -> bool {
+        self.cost[cell].is_infinite()
+    }
+
+    // Step cost from `cell` in direction `d`, or None if the move is not allowed
+    fn step(&self, cell: usize, d: (i32, i32)) -> Option<(usize, f64)> {
+        let next = self.neighbor(cell, d)?;
+        if self.blocked(next) && Some(next) != self.goal {
+            return None;
+        }
+        let diagonal = d.0 != 0 && d.1 != 0;
+        if diagonal {
+            let side_x = self.neighbor(cell, (d.0, 0))?;
+            let side_y = self.neighbor(cell, (0, d.1))?;
+            if self.blocked(side_x) || self.blocked(side_y) {
+                return None;
+            }
+        }
+        let length = if diagonal { std::f64::consts::SQRT_2 } else { 1.0 };
+        // The goal cell may sit under an obstacle (ship over a wall); crossing it costs 1
+        let cost = |c: usize| if self.blocked(c) { 1.0 } else { self.cost[c] };
+        Some((next, length * (cost(cell) + cost(next)) * 0.5))
+    }
+
+    // Match the grid to the canvas and rasterise the regions
+    fn rasterise(&mut self, width: f64, height: f64) {
+        self.cols = (width / self.cell_size).ceil().max(1.0) as usize;
+        self.rows = (height / self.cell_size).ceil().max(1.0) as usize;
+        let cells = self.cols * self.rows;
+        self.cost.clear();
+        self.cost.resize(cells, 1.0);
+
+        for region in &self.regions {
+            let x0 = (region.x / self.cell_size).floor().max(0.0) as usize;
+            let y0 = (region.y / self.cell_size).floor().max(0.0) as usize;
+            let x1 = (((region.x + region.w) / self.cell_size).ceil() as usize).min(self.cols);
+            let y1 = (((region.y + region.h) / self.cell_size).ceil() as usize).min(self.rows);
+            let value = match region.kind {
+                RegionKind::Blocked => f64::INFINITY,
+                RegionKind::Cost(cost) => cost,
+            };
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    self.cost[y * self.cols + x] = value;
+                }
+            }
+        }
+
+        self.dist.resize(cells, f64::INFINITY);
+        self.dirs.resize(cells, Vec2::ZERO);
+        self.dirty = false;
+        self.goal = None;
+    }
+
+    // Dijkstra outward from the goal cell, then point every cell at its cheapest neighbour
+    fn rebuild(&mut self, goal: usize) {
+        self.goal = Some(goal);
+        self.dist.iter_mut().for_each(|d| *d = f64::INFINITY);
+        self.dirs.iter_mut().for_each(|d| *d = Vec2::ZERO);
+
+        let mut open = BinaryHeap::new();
+        self.dist[goal] = 0.0;
+        open.push(Frontier { dist: 0.0, cell: goal });
+        while let Some(Frontier { dist, cell }) = open.pop() {
+            if dist > self.dist[cell] {
+                continue;
+            }
+            for d in NEIGHBORS {
+                let Some((next, cost)) = self.step(cell, d) else {
+                    continue;
+                };
+                // Moves are symmetric, so the cost from next back to cell is the same
+                let candidate = dist + cost;
+                if candidate < self.dist[next] {
+                    self.dist[next] = candidate;
+                    open.push(Frontier { dist: candidate, cell: next });
+                }
+            }
+        }
+
+        for cell in 0..self.dist.len() {
+            if cell == goal || self.dist[cell].is_infinite() {
+                continue;
+            }
+            let best = NEIGHBORS
+                .iter()
+                .filter_map(|&d| self.step(cell, d))
+                .min_by(|a, b| self.dist[a.0].total_cmp(&self.dist[b.0]));
+            if let Some((next, _)) = best {
+                self.dirs[cell] = (self.center(next) - self.center(cell)).normalized();
+            }
+        }
+    }
+
+    // Rebuild if the canvas or regions changed or the goal moved to another cell
+    pub fn update(&mut self, goal_x: f64, goal_y: f64, width: f64, height: f64) {
+        let cols = (width / self.cell_size).ceil().max(1.0) as usize;
+        let rows = (height / self.cell_size).ceil().max(1.0) as usize;
+        if self.dirty || cols != self.cols || rows != self.rows {
+            self.rasterise(width, height);
+        }
+
+        self.goal_pos = Vec2::new(goal_x, goal_y);
+        let gx = (goal_x / self.cell_size).floor().clamp(0.0, (self.cols - 1) as f64) as usize;
+        let gy = (goal_y / self.cell_size).floor().clamp(0.0, (self.rows - 1) as f64) as usize;
+        let goal = gy * self.cols + gx;
+        if self.goal != Some(goal) {
+            self.rebuild(goal);
+        }
+    }
+
+    // Direction of a reachable cell; the goal cell points straight at the goal itself
+    fn cell_direction(&self, cell: usize, pos: Vec2) -> Option<Vec2> {
+        if Some(cell) == self.goal {
+            return Some((self.goal_pos - pos).normalized());
+        }
+        self.dist[cell].is_finite().then_some(self.dirs[cell])
+    }
+
+    // Unit heading towards the goal at (x, y), blended over the four nearest cell centres so
+    // paths curve instead of snapping between the eight grid directions. None outside the
+    // grid or where the goal cannot be reached, so callers fall back to a straight line.
+    pub fn sample(&self, x: f64, y: f64) -> Option<Vec2> {
+        let own = self.cell_at(x, y)?;
+        self.cell_direction(own, Vec2::new(x, y))?;
+
+        let pos = Vec2::new(x, y);
+        let fx = x / self.cell_size - 0.5;
+        let fy = y / self.cell_size - 0.5;
+        let (x0, y0) = (fx.floor(), fy.floor());
+        let (tx, ty) = (fx - x0, fy - y0);
+
+        let mut blended = Vec2::ZERO;
+        let corners = [
+            (0.0, 0.0, (1.0 - tx) * (1.0 - ty)),
+            (1.0, 0.0, tx * (1.0 - ty)),
+            (0.0, 1.0, (1.0 - tx) * ty),
+            (1.0, 1.0, tx * ty),
+        ];
+        for (dx, dy, weight) in corners {
+            let cx = x0 + dx;
+            let cy = y0 + dy;
+            if cx < 0.0 || cy < 0.0 || cx >= self.cols as f64 || cy >= self.rows as f64 {
+                continue;
+            }
+            let cell = cy as usize * self.cols + cx as usize;
+            if let Some(dir) = self.cell_direction(cell, pos) {
+                blended += dir * weight;
+            }
+        }
+
+        let dir = blended.normalized();
+        if dir == Vec2::ZERO {
+            self.cell_direction(own, pos)
+        } else {
+            Some(dir)
+        }
+    }
+}
+
+thread_local! {
+    static FLOW_FIELD: RefCell<FlowField> = RefCell::new(FlowField::default());
+}
+
+// Bring the field up to date for this tick and lend it to `f`; None when nothing is marked
+pub fn with_field<R>(goal_x: f64, goal_y: f64, width: f64, height: f64, f: impl FnOnce(Option<&FlowField>) -> R) -> R {
+    FLOW_FIELD.with(|field| {
+        let mut field = field.borrow_mut();
+        if !field.is_active() {
+            return f(None);
+        }
+        field.update(goal_x, goal_y, width, height);
+        f(Some(&field))
+    })
+}
+
+pub fn with_field_mut<R>(f: impl FnOnce(&mut FlowField) -> R) -> R {
+    FLOW_FIELD.with(|field| f(&mut field.borrow_mut()))
+}
+
+// Replaces the field; regions are kept and re-rasterised at the new size
+pub fn set_cell_size(cell_size: f64) -> Result<(), String> {
+    if !(MIN_CELL_SIZE..=MAX_CELL_SIZE).contains(&cell_size) {
+        return Err(format!("flow field cell size {} is outside [{}, {}]", cell_size, MIN_CELL_SIZE, MAX_CELL_SIZE));
+    }
+    with_field_mut(|field| {
+        let regions = std::mem::take(&mut field.regions);
+        *field = FlowField::new(cell_size);
+        field.regions = regions;
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CELL: f64 = 40.0;
+
+    // Field of cols x rows cells with the goal in the centre of cell (gx, gy)
+    fn build(cols: usize, rows: usize, setup: impl FnOnce(&mut FlowField), gx: usize, gy: usize) -> FlowField {
+        let mut field = FlowField::new(CELL);
+        setup(&mut field);
+        field.update((gx as f64 + 0.5) * CELL, (gy as f64 + 0.5) * CELL, cols as f64 * CELL, rows as f64 * CELL);
+        field
+    }
+
+    fn dist(field: &FlowField, x: usize, y: usize) -> f64 {
+        field.dist[y * field.cols + x]
+    }
+
+    #[test]
+    fn crossing_a_cost_region_costs_its_average() {
+        let field = build(3, 1, |f| f.add_cost(CELL, 0.0, CELL, CELL, 5.0).unwrap(), 2, 0);
+        assert_eq!(dist(&field, 1, 0), 3.0);
+        assert_eq!(dist(&field, 0, 0), 6.0);
+    }
+
+    #[test]
+    fn paths_detour_around_expensive_ground() {
+        // Expensive column between start and goal except along the bottom row
+        let field = build(3, 3, |f| f.add_cost(CELL, 0.0, CELL, 2.0 * CELL, 100.0).unwrap(), 2, 0);
+        let dir = field.dirs[0];
+        assert!(dir.x == 0.0 && dir.y > 0.0, "expected to head down first, got {:?}", dir);
+        assert!(dist(&field, 0, 0) < 10.0);
+    }
+
+    #[test]
+    fn diagonals_do_not_cut_blocked_corners() {
+        // Top-right blocked: top-left may not slip diagonally past it to the bottom-right goal
+        let field = build(2, 2, |f| f.add_blocked(CELL, 0.0, CELL, CELL).unwrap(), 1, 1);
+        assert_eq!(dist(&field, 0, 0), 2.0);
+        assert_eq!(field.dirs[0], Vec2::new(0.0, 1.0));
+
+        // With nothing blocked the diagonal is taken
+        let open = build(2, 2, |_| {}, 1, 1);
+        assert_eq!(dist(&open, 0, 0), std::f64::consts::SQRT_2);
+    }
+
+    #[test]
+    fn walled_off_cells_are_unreachable() {
+        let field = build(3, 3, |f| f.add_blocked(CELL, 0.0, CELL, 3.0 * CELL).unwrap(), 2, 1);
+        for y in 0..3 {
+            assert!(dist(&field, 0, y).is_infinite());
+        }
+        assert_eq!(field.sample(0.5 * CELL, 1.5 * CELL), None);
+        assert!(field.sample(2.2 * CELL, 0.5 * CELL).is_some());
+    }
+
+    #[test]
+    fn regions_reject_non_finite_and_negative_rects() {
+        let mut field = FlowField::default();
+        assert!(field.add_blocked(f64::NAN, 0.0, 10.0, 10.0).is_err());
+        assert!(field.add_blocked(0.0, f64::INFINITY, 10.0, 10.0).is_err());
+        assert!(field.add_blocked(0.0, 0.0, -10.0, 10.0).is_err());
+        assert!(field.add_cost(0.0, 0.0, 10.0, f64::NAN, 2.0).is_err());
+        assert!(field.add_cost(0.0, 0.0, 10.0, -1.0, 2.0).is_err());
+        assert!(field.add_cost(0.0, 0.0, 10.0, 10.0, 0.5).is_err());
+        assert!(field.regions.is_empty());
+        assert!(field.add_blocked(0.0, 0.0, 0.0, 10.0).is_ok());
+    }
+}
@@ -0,0 +1,358 @@
+// Module formation solver - where each module should be and how it gets there
+// Docked modules orbit the ship on a rotating ring; deployed (split) modules hold the
+// selected formation. Slots are eased in the formation's local frame so that adding or
+// losing a module re-spaces the others smoothly instead of snapping their targets.
+use serde::{Serialize, Deserialize};
+
+use crate::grid::EnemyGrid;
+use crate::module_ai::find_nearest_enemy_target;
+use crate::steering::Vec2;
+use crate::types::*;
+
+// Layout used while deployed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Formation {
+    // Fixed ring around the point where the ship split (the original behavior)
+    Ring = 0,
+    // Row across the ship's heading, ahead of it
+    Line = 1,
+    // V with its tip ahead of the ship
+    Wedge = 2,
+    // Arc between the ship and the nearest threat
+    Escort = 3,
+}
+
+impl From<u32> for Formation {
+    fn from(v: u32) -> Self {
+        match v {
+            1 => Formation::Line,
+            2 => Formation::Wedge,
+            3 => Formation::Escort,
+            _ => Formation::Ring,
+        }
+    }
+}
+
+// Movement tunables; the first five mirror MODULE in shipSystem.js
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct FormationConfig {
+    pub lerp: f64,
+    pub split_lerp: f64,
+    pub recall_lerp: f64,
+    pub recall_frames: f64,
+    pub max_speed: f64,
+    // Docked orbit
+    pub ring_radius: f64,
+    pub rotation_speed: f64,
+    // Deployed ring radius
+    pub split_radius: f64,
+    // Gap between neighbouring line / wedge slots
+    pub spacing: f64,
+    // How far ahead of the ship the line / wedge tip sits
+    pub lead_distance: f64,
+    // Escort arc radius around the ship and angle between neighbouring slots
+    pub escort_radius: f64,
+    pub escort_spread: f64,
+    // Fraction of the remaining slot offset closed per frame (1 = snap)
+    pub transition_rate: f64,
+}
+
+impl Default for FormationConfig {
+    fn default() -> Self {
+        Self {
+            lerp: 0.28,
+            split_lerp: 0.24,
+            recall_lerp: 0.40,
+            recall_frames: 45.0,
+            max_speed: 10.0,
+            ring_radius: 40.0,
+            rotation_speed: 0.02,
+            split_radius: 150.0,
+            spacing: 50.0,
+            lead_distance: 90.0,
+            escort_radius: 90.0,
+            escort_spread: 0.6,
+            transition_rate: 0.12,
+        }
+    }
+}
+
+impl FormationConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        fn check(name: &str, value: f64, min: f64, max: f64) -> Result<(), String> {
+            if value.is_finite() && value >= min && value <= max {
+                Ok(())
+            } else {
+                Err(format!("FormationConfig.{} = {} is outside [{}, {}]", name, value, min, max))
+            }
+        }
+
+        check("LERP", self.lerp, 0.01, 1.0)?;
+        check("SPLIT_LERP", self.split_lerp, 0.01, 1.0)?;
+        check("RECALL_LERP", self.recall_lerp, 0.01, 1.0)?;
+        check("RECALL_FRAMES", self.recall_frames, 0.0, 600.0)?;
+        check("MAX_SPEED", self.max_speed, 0.1, 100.0)?;
+        check("RING_RADIUS", self.ring_radius, 0.0, 1000.0)?;
+        check("ROTATION_SPEED", self.rotation_speed, -1.0, 1.0)?;
+        check("SPLIT_RADIUS", self.split_radius, 0.0, 2000.0)?;
+        check("SPACING", self.spacing, 0.0, 500.0)?;
+        check("LEAD_DISTANCE", self.lead_distance, -1000.0, 1000.0)?;
+        check("ESCORT_RADIUS", self.escort_radius, 0.0, 1000.0)?;
+        check("ESCORT_SPREAD", self.escort_spread, 0.0, TWO_PI)?;
+        check("TRANSITION_RATE", self.transition_rate, 0.01, 1.0)?;
+        Ok(())
+    }
+}
+
+// Output record: [target_x, target_y, x, y]
+pub const FORMATION_STRIDE: usize = 4;
+
+// Heading used before the ship has moved: straight up the screen
+const DEFAULT_HEADING: f64 = -HALF_PI;
+// Ship speed (px/frame) below which the last heading is kept
+const HEADING_MIN_SPEED: f64 = 0.5;
+
+#[derive(Debug, Clone)]
+pub struct FormationSolver {
+    pub config: FormationConfig,
+    pub formation: Formation,
+    rotation: f64,
+    heading: f64,
+    deployed: bool,
+    // Where the ship was when it split; centre of the deployed ring
+    anchor: Vec2,
+    // Per-module eased slot offsets in the formation's local frame
+    slots: Vec<Vec2>,
+    recalling: Vec<f64>,
+}
+
+impl Default for FormationSolver {
+    fn default() -> Self {
+        Self {
+            config: FormationConfig::default(),
+            formation: Formation::Ring,
+            rotation: 0.0,
+            heading: DEFAULT_HEADING,
+            deployed: false,
+            anchor: Vec2::ZERO,
+            slots: Vec::new(),
+            recalling: Vec::new(),
+        }
+    }
+}
+
+impl FormationSolver {
+    // Local offset of slot i of n; x points along the frame angle
+    fn slot_offset(&self, i: usize, n: usize) -> Vec2 {
+        let cfg = &self.config;
+        if !self.deployed {
+            return Vec2::from_angle(TWO_PI / n as f64 * i as f64) * cfg.ring_radius;
+        }
+
+        match self.formation {
+            Formation::Ring => Vec2::from_angle(TWO_PI / n as f64 * i as f64) * cfg.split_radius,
+            Formation::Line => {
+                let side = i as f64 - (n as f64 - 1.0) * 0.5;
+                Vec2::new(cfg.lead_distance, side * cfg.spacing)
+            }
+            Formation::Wedge => {
+                // Tip first, then alternating sides, each rank one spacing further back
+                let rank = i.div_ceil(2) as f64;
+                let side = if i % 2 == 1 { -1.0 } else { 1.0 };
+                Vec2::new(cfg.lead_distance - rank * cfg.spacing * 0.8, side * rank * cfg.spacing)
+            }
+            Formation::Escort => {
+                let side = i as f64 - (n as f64 - 1.0) * 0.5;
+                Vec2::from_angle(side * cfg.escort_spread) * cfg.escort_radius
+            }
+        }
+    }
+
+    // Origin and rotation of the local frame this frame
+    fn frame(&self, ship: Vec2, threat: Option<Vec2>) -> (Vec2, f64) {
+        if !self.deployed {
+            return (ship, self.rotation);
+        }
+        match self.formation {
+            Formation::Ring => (self.anchor, 0.0),
+            Formation::Line | Formation::Wedge => (ship, self.heading),
+            Formation::Escort => match threat {
+                Some(t) if t != ship => (ship, (t.y - ship.y).atan2(t.x - ship.x)),
+                _ => (ship, self.heading),
+            },
+        }
+    }
+
+    // Deploy / recall follow the `deployed` flag (MotherShip.isSplit): the rising edge
+    // anchors the ring at the ship, the falling edge starts recall on every module.
+    fn set_deployed(&mut self, deployed: bool, ship: Vec2) {
+        if deployed == self.deployed {
+            return;
+        }
+        self.deployed = deployed;
+        if deployed {
+            self.anchor = ship;
+        } else {
+            self.recalling.iter_mut().for_each(|r| *r = self.config.recall_frames);
+        }
+        // The layout changed wholesale; snap slots and let module movement do the easing
+        self.slots.clear();
+    }
+
+    // Match per-module state to the module count. Modules carry no identity and a removed
+    // one can come from the middle, so on a count change the surviving slots are re-seeded
+    // from where each module is now in the local frame; they then ease to their new spacing
+    // from their own positions. New (appended) slots and a fresh layout start in place.
+    fn resize(&mut self, module_data: &[f64], origin: Vec2, angle: f64) {
+        let n = module_data.len() / 3;
+        self.recalling.resize(n, 0.0);
+        if self.slots.len() == n {
+            return;
+        }
+
+        let kept = self.slots.len().min(n);
+        let (sin, cos) = angle.sin_cos();
+        self.slots.clear();
+        for (i, module) in module_data.chunks_exact(3).enumerate() {
+            let slot = if i < kept {
+                let d = Vec2::new(module[0], module[1]) - origin;
+                Vec2::new(d.x * cos + d.y * sin, d.y * cos - d.x * sin)
+            } else {
+                self.slot_offset(i, n)
+            };
+            self.slots.push(slot);
+        }
+    }
+
+    // One frame: advance the orbit, ease slots, move every module towards its target.
+    // module_data is [x, y, radius] per module and is updated in place; `out` receives
+    // one FORMATION_STRIDE record per module.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        module_data: &mut [f64],
+        enemy_data: &[f64],
+        ship_x: f64,
+        ship_y: f64,
+        ship_vx: f64,
+        ship_vy: f64,
+        deployed: bool,
+        grid: Option<&EnemyGrid>,
+        out: &mut Vec<f64>,
+    ) {
+        let ship = Vec2::new(ship_x, ship_y);
+        let n = module_data.len() / 3;
+
+        self.rotation += self.config.rotation_speed;
+        if hypot(ship_vx, ship_vy) > HEADING_MIN_SPEED {
+            self.heading = ship_vy.atan2(ship_vx);
+        }
+        self.set_deployed(deployed, ship);
+
+        let threat = if self.deployed && self.formation == Formation::Escort {
+            let target = find_nearest_enemy_target(ship_x, ship_y, enemy_data, grid);
+            target.has_target.then(|| Vec2::new(target.target_x, target.target_y))
+        } else {
+            None
+        };
+        let (origin, angle) = self.frame(ship, threat);
+        self.resize(module_data, origin, angle);
+        let (sin, cos) = angle.sin_cos();
+        let cfg = self.config;
+
+        for (i, module) in module_data.chunks_exact_mut(3).enumerate() {
+            let desired = self.slot_offset(i, n);
+            let slot = self.slots[i] + (desired - self.slots[i]) * cfg.transition_rate;
+            self.slots[i] = slot;
+            let target = origin + Vec2::new(slot.x * cos - slot.y * sin, slot.x * sin + slot.y * cos);
+
+            // Same capped lerp as Module.update
+            let lerp = if self.recalling[i] > 0.0 {
+                cfg.recall_lerp
+            } else if self.deployed {
+                cfg.split_lerp
+            } else {
+                cfg.lerp
+            };
+            let pos = Vec2::new(module[0], module[1]);
+            let step = ((target - pos) * lerp).truncate(cfg.max_speed);
+            let pos = pos + step;
+            module[0] = pos.x;
+            module[1] = pos.y;
+            if self.recalling[i] > 0.0 {
+                self.recalling[i] -= 1.0;
+            }
+
+            out.extend_from_slice(&[target.x, target.y, pos.x, pos.y]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modules(positions: &[(f64, f64)]) -> Vec<f64> {
+        positions.iter().flat_map(|&(x, y)| [x, y, 8.0]).collect()
+    }
+
+    fn step(solver: &mut FormationSolver, module_data: &mut [f64]) -> Vec<f64> {
+        let mut out = Vec::new();
+        solver.update(module_data, &[], 400.0, 300.0, 0.0, 0.0, false, None, &mut out);
+        out
+    }
+
+    fn target(out: &[f64], i: usize) -> Vec2 {
+        Vec2::new(out[i * FORMATION_STRIDE], out[i * FORMATION_STRIDE + 1])
+    }
+
+    fn settled(count: usize) -> (FormationSolver, Vec<f64>) {
+        let mut solver = FormationSolver::default();
+        let mut module_data = modules(&vec![(400.0, 300.0); count]);
+        for _ in 0..200 {
+            step(&mut solver, &mut module_data);
+        }
+        (solver, module_data)
+    }
+
+    #[test]
+    fn slots_ease_to_the_new_spacing() {
+        let (mut solver, mut module_data) = settled(3);
+        let before = target(&step(&mut solver, &mut module_data), 1);
+        module_data.extend_from_slice(&[400.0, 300.0, 8.0]);
+        let out = step(&mut solver, &mut module_data);
+
+        // Slot 1 moves from 120 to 90 degrees round the ring, but only by transition_rate per frame
+        let cfg = FormationConfig::default();
+        let jump = (target(&out, 1) - before).length();
+        assert!(jump > 0.0 && jump < cfg.ring_radius * cfg.transition_rate);
+
+        let mut last = out;
+        for _ in 0..200 {
+            last = step(&mut solver, &mut module_data);
+        }
+        let angle = solver.rotation;
+        let desired = Vec2::new(400.0, 300.0) + Vec2::from_angle(angle + HALF_PI) * cfg.ring_radius;
+        assert!((target(&last, 1) - desired).length() < 1e-6);
+    }
+
+    #[test]
+    fn removing_a_middle_module_keeps_the_others_in_place() {
+        let (mut solver, mut module_data) = settled(3);
+        step(&mut solver, &mut module_data);
+        let first = Vec2::new(module_data[0], module_data[1]);
+        let middle = Vec2::new(module_data[3], module_data[4]);
+        let last = Vec2::new(module_data[6], module_data[7]);
+
+        let mut module_data = modules(&[(first.x, first.y), (last.x, last.y)]);
+        let out = step(&mut solver, &mut module_data);
+        let cfg = FormationConfig::default();
+
+        // The former last module eases from its own spot, not from the removed module's slot
+        let moved = target(&out, 1);
+        assert!((moved - last).length() < cfg.ring_radius * cfg.transition_rate * 2.0);
+        assert!((moved - middle).length() > cfg.ring_radius);
+        assert!((target(&out, 0) - first).length() < cfg.ring_radius * cfg.transition_rate * 2.0);
+    }
+}
@@ -0,0 +1,150 @@
+// Enemy decision state machine - explicit modes persisted in the enemy record
+// (ai_mode at offset 22, frames spent in it at offset 23). Every change is reported
+// as a STATE_CHANGED event so the host can telegraph it or show it in the dev overlay.
+use crate::events::*;
+use rng::random;
+use crate::types::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiMode {
+    // Default steering toward the target
+    Cruise = 0,
+    // Winding up a charge: slows and turns to face the target
+    Telegraph = 1,
+    // Boosted dash, lasts charge_frames
+    Charge = 2,
+    // Boosted top speed after a bounce or a finished charge
+    Rebound = 3,
+    // Close to the ship: tangential drift is damped so it drives straight in
+    OrbitBreak = 4,
+    // Peeling away after landing a tip hit
+    Retreat = 5,
+}
+
+impl From<f64> for AiMode {
+    fn from(v: f64) -> Self {
+        match v as i32 {
+            1 => AiMode::Telegraph,
+            2 => AiMode::Charge,
+            3 => AiMode::Rebound,
+            4 => AiMode::OrbitBreak,
+            5 => AiMode::Retreat,
+            _ => AiMode::Cruise,
+        }
+    }
+}
+
+// Switch modes, reset the frame counter and report the transition
+pub fn transition(index: usize, enemy: &mut EnemyState, to: AiMode, events: &mut Vec<AiEvent>) {
+    if enemy.ai_mode == to {
+        return;
+    }
+    events.push(AiEvent::StateChanged { index, from: enemy.ai_mode, to, x: enemy.x, y: enemy.y });
+    enemy.ai_mode = to;
+    enemy.state_frames = 0.0;
+}
+
+// Rammer transitions taken at the start of its update:
+//   Cruise/OrbitBreak -> Telegraph -> Charge -> Rebound -> Cruise
+//   Retreat -> Cruise once retreat_frames have passed
+// Bounces (-> Rebound) and tip hits (-> Retreat) are applied by the collision code.
+pub fn next_rammer_mode(enemy: &EnemyState, cfg: &RammerConfig, dist_to_ship: f64, aggr: f64) -> AiMode {
+    match enemy.ai_mode {
+        AiMode::Telegraph if enemy.state_frames >= cfg.telegraph_frames => AiMode::Charge,
+        AiMode::Telegraph => AiMode::Telegraph,
+        AiMode::Charge if enemy.charge_frames > 0.0 => AiMode::Charge,
+        AiMode::Charge | AiMode::Rebound if enemy.bounce_boost_frames > 0.0 => AiMode::Rebound,
+        AiMode::Charge | AiMode::Rebound => AiMode::Cruise,
+        AiMode::Retreat if enemy.state_frames < cfg.retreat_frames => AiMode::Retreat,
+        AiMode::Cruise | AiMode::OrbitBreak | AiMode::Retreat => {
+            if enemy.charge_cooldown <= 0.0 && dist_to_ship < cfg.charge_dist && random() < cfg.charge_prob * aggr {
+                if cfg.telegraph_frames > 0.0 {
+                    AiMode::Telegraph
+                } else {
+                    AiMode::Charge
+                }
+            } else if dist_to_ship < cfg.orbit_break_radius {
+                AiMode::OrbitBreak
+            } else {
+                AiMode::Cruise
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rammer(mode: AiMode, state_frames: f64) -> EnemyState {
+        let mut enemy = EnemyState::from_slice(&[0.0; ENEMY_STRIDE]);
+        enemy.ai_mode = mode;
+        enemy.state_frames = state_frames;
+        enemy
+    }
+
+    #[test]
+    fn cruise_telegraphs_a_charge_then_charges() {
+        let cfg = RammerConfig { charge_prob: 1.0, ..RammerConfig::default() };
+        let far = cfg.charge_dist + 1.0;
+        let near = cfg.orbit_break_radius + 1.0;
+
+        assert_eq!(next_rammer_mode(&rammer(AiMode::Cruise, 0.0), &cfg, far, 1.0), AiMode::Cruise);
+        assert_eq!(next_rammer_mode(&rammer(AiMode::Cruise, 0.0), &cfg, near, 1.0), AiMode::Telegraph);
+        let early = rammer(AiMode::Telegraph, cfg.telegraph_frames - 1.0);
+        assert_eq!(next_rammer_mode(&early, &cfg, near, 1.0), AiMode::Telegraph);
+        let ready = rammer(AiMode::Telegraph, cfg.telegraph_frames);
+        assert_eq!(next_rammer_mode(&ready, &cfg, near, 1.0), AiMode::Charge);
+
+        // No wind-up when TELEGRAPH_FRAMES is 0
+        let instant = RammerConfig { telegraph_frames: 0.0, ..cfg };
+        assert_eq!(next_rammer_mode(&rammer(AiMode::Cruise, 0.0), &instant, near, 1.0), AiMode::Charge);
+    }
+
+    #[test]
+    fn charge_ends_in_rebound_then_cruise() {
+        let cfg = RammerConfig { charge_prob: 0.0, ..RammerConfig::default() };
+        let far = cfg.charge_dist + 1.0;
+
+        let mut enemy = rammer(AiMode::Charge, 5.0);
+        enemy.charge_frames = 3.0;
+        assert_eq!(next_rammer_mode(&enemy, &cfg, far, 1.0), AiMode::Charge);
+        enemy.charge_frames = 0.0;
+        enemy.bounce_boost_frames = 2.0;
+        assert_eq!(next_rammer_mode(&enemy, &cfg, far, 1.0), AiMode::Rebound);
+        enemy.ai_mode = AiMode::Rebound;
+        enemy.bounce_boost_frames = 0.0;
+        assert_eq!(next_rammer_mode(&enemy, &cfg, far, 1.0), AiMode::Cruise);
+    }
+
+    #[test]
+    fn retreat_lasts_retreat_frames_and_orbit_break_is_by_distance() {
+        let cfg = RammerConfig { charge_prob: 0.0, ..RammerConfig::default() };
+        let close = cfg.orbit_break_radius - 1.0;
+
+        let retreating = rammer(AiMode::Retreat, cfg.retreat_frames - 1.0);
+        assert_eq!(next_rammer_mode(&retreating, &cfg, close, 1.0), AiMode::Retreat);
+        let done = rammer(AiMode::Retreat, cfg.retreat_frames);
+        assert_eq!(next_rammer_mode(&done, &cfg, close, 1.0), AiMode::OrbitBreak);
+        let orbiting = rammer(AiMode::OrbitBreak, 10.0);
+        assert_eq!(next_rammer_mode(&orbiting, &cfg, cfg.orbit_break_radius, 1.0), AiMode::Cruise);
+    }
+
+    #[test]
+    fn transition_reports_changes_and_resets_the_frame_count() {
+        let mut enemy = rammer(AiMode::Cruise, 30.0);
+        enemy.x = 12.0;
+        let mut events = Vec::new();
+
+        transition(4, &mut enemy, AiMode::Cruise, &mut events);
+        assert!(events.is_empty());
+        assert_eq!(enemy.state_frames, 30.0);
+
+        transition(4, &mut enemy, AiMode::Telegraph, &mut events);
+        assert_eq!(
+            events,
+            vec![AiEvent::StateChanged { index: 4, from: AiMode::Cruise, to: AiMode::Telegraph, x: 12.0, y: 0.0 }]
+        );
+        assert_eq!((enemy.ai_mode, enemy.state_frames), (AiMode::Telegraph, 0.0));
+    }
+}
@@ -4,6 +4,10 @@ use std::collections::HashMap;
 pub struct EnemyGrid {
     cell_size: f64,
     grid: HashMap<(i32, i32), Vec<usize>>,
+    count: usize,
+    // Occupied cell bounds, limits how far a ring search has to go
+    min_cell: (i32, i32),
+    max_cell: (i32, i32),
 }
 
 impl EnemyGrid {
@@ -11,6 +15,9 @@ impl EnemyGrid {
         Self {
             cell_size,
             grid: HashMap::new(),
+            count: 0,
+            min_cell: (0, 0),
+            max_cell: (0, 0),
         }
     }
 
@@ -22,33 +29,186 @@ impl EnemyGrid {
         )
     }
 
+    // Changing the cell size drops the cells; rebuild before querying again
+    pub fn set_cell_size(&mut self, cell_size: f64) {
+        if cell_size != self.cell_size {
+            self.cell_size = cell_size;
+            self.grid.clear();
+            self.count = 0;
+        }
+    }
+
+    // Cells are emptied rather than dropped so a grid rebuilt every tick keeps its allocations
     pub fn rebuild(&mut self, positions: &[f64]) {
-        self.grid.clear();
+        self.grid.values_mut().for_each(Vec::clear);
         let num_enemies = positions.len() / 2;
+        self.count = num_enemies;
+        self.min_cell = (i32::MAX, i32::MAX);
+        self.max_cell = (i32::MIN, i32::MIN);
 
         for i in 0..num_enemies {
             let x = positions[i * 2];
             let y = positions[i * 2 + 1];
             let cell = self.get_cell(x, y);
 
-            self.grid.entry(cell).or_insert_with(Vec::new).push(i);
+            self.min_cell = (self.min_cell.0.min(cell.0), self.min_cell.1.min(cell.1));
+            self.max_cell = (self.max_cell.0.max(cell.0), self.max_cell.1.max(cell.1));
+            self.grid.entry(cell).or_default().push(i);
         }
     }
 
-    pub fn query_neighbors(&self, x: f64, y: f64) -> Vec<usize> {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    // Nearest entry by expanding square rings of cells around (x, y).
+    // `dist_sq(i)` returns the squared distance of an acceptable entry or None to skip it.
+    // Ties go to the lower index, so the result matches a linear scan over the same positions.
+    pub fn nearest_by(&self, x: f64, y: f64, mut dist_sq: impl FnMut(usize) -> Option<f64>) -> Option<(usize, f64)> {
+        if self.is_empty() {
+            return None;
+        }
+
         let (cx, cy) = self.get_cell(x, y);
-        let mut neighbors = Vec::new();
-
-        // Query 3x3 grid
-        for dy in -1..=1 {
-            for dx in -1..=1 {
-                let cell = (cx + dx, cy + dy);
-                if let Some(indices) = self.grid.get(&cell) {
-                    neighbors.extend(indices);
+        let max_ring = (cx - self.min_cell.0)
+            .abs()
+            .max((self.max_cell.0 - cx).abs())
+            .max((cy - self.min_cell.1).abs())
+            .max((self.max_cell.1 - cy).abs());
+
+        let mut best: Option<(usize, f64)> = None;
+        let mut visit = |cell: (i32, i32), best: &mut Option<(usize, f64)>| {
+            let Some(indices) = self.grid.get(&cell) else {
+                return;
+            };
+            for &i in indices {
+                let Some(d) = dist_sq(i) else {
+                    continue;
+                };
+                if best.is_none_or(|(bi, bd)| d < bd || (d == bd && i < bi)) {
+                    *best = Some((i, d));
+                }
+            }
+        };
+
+        for ring in 0..=max_ring {
+            // (x, y) may sit on its cell's edge, so ring cells are at least ring - 1 cells away
+            if let Some((_, best_d)) = best {
+                let gap = (ring - 1) as f64 * self.cell_size;
+                if gap > 0.0 && best_d < gap * gap {
+                    break;
                 }
             }
+
+            if ring == 0 {
+                visit((cx, cy), &mut best);
+                continue;
+            }
+            for dx in -ring..=ring {
+                visit((cx + dx, cy - ring), &mut best);
+                visit((cx + dx, cy + ring), &mut best);
+            }
+            for dy in (-ring + 1)..ring {
+                visit((cx - ring, cy + dy), &mut best);
+                visit((cx + ring, cy + dy), &mut best);
+            }
+        }
+
+        best
+    }
+
+    pub fn query_neighbors(&self, x: f64, y: f64) -> Vec<usize> {
+        self.neighbors(x, y).collect()
+    }
+
+    // Same as query_neighbors, reusing `out`
+    pub fn query_neighbors_into(&self, x: f64, y: f64, out: &mut Vec<usize>) {
+        out.clear();
+        out.extend(self.neighbors(x, y));
+    }
+
+    // Entries in the 3x3 block of cells around (x, y), without allocating
+    pub fn neighbors(&self, x: f64, y: f64) -> impl Iterator<Item = usize> + '_ {
+        let (cx, cy) = self.get_cell(x, y);
+        (-1..=1)
+            .flat_map(move |dy| (-1..=1).map(move |dx| (cx + dx, cy + dy)))
+            .filter_map(|cell| self.grid.get(&cell))
+            .flatten()
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    // Lattice coordinates so equal distances (ties) come up often, including negative cells
+    fn lattice(rng: &mut Rng) -> f64 {
+        (rng.next_f64() * 80.0).floor() * 16.0 - 300.0
+    }
+
+    fn brute_force(positions: &[f64], x: f64, y: f64, skip: &[bool]) -> Option<(usize, f64)> {
+        let mut best: Option<(usize, f64)> = None;
+        for i in 0..positions.len() / 2 {
+            if skip[i] {
+                continue;
+            }
+            let (dx, dy) = (positions[i * 2] - x, positions[i * 2 + 1] - y);
+            let d = dx * dx + dy * dy;
+            if best.is_none_or(|(_, bd)| d < bd) {
+                best = Some((i, d));
+            }
         }
+        best
+    }
+
+    #[test]
+    fn nearest_by_matches_brute_force() {
+        let mut rng = Rng::new(17);
+        let mut grid = EnemyGrid::new(128.0);
+        for _ in 0..200 {
+            let count = 1 + (rng.next_f64() * 60.0) as usize;
+            let positions: Vec<f64> = (0..count * 2).map(|_| lattice(&mut rng)).collect();
+            let skip: Vec<bool> = (0..count).map(|_| rng.next_f64() < 0.25).collect();
+            grid.rebuild(&positions);
+
+            for _ in 0..20 {
+                let (x, y) = (lattice(&mut rng), lattice(&mut rng));
+                let found = grid.nearest_by(x, y, |i| {
+                    let (dx, dy) = (positions[i * 2] - x, positions[i * 2 + 1] - y);
+                    (!skip[i]).then_some(dx * dx + dy * dy)
+                });
+                assert_eq!(found, brute_force(&positions, x, y, &skip));
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_by_breaks_ties_by_index() {
+        let mut grid = EnemyGrid::new(128.0);
+        // Four entries at the same distance from the origin, in four different cells
+        let positions = [100.0, 0.0, 0.0, -100.0, -100.0, 0.0, 0.0, 100.0];
+        grid.rebuild(&positions);
+        let found = grid.nearest_by(0.0, 0.0, |i| Some(positions[i * 2].powi(2) + positions[i * 2 + 1].powi(2)));
+        assert_eq!(found, Some((0, 10_000.0)));
+        let found = grid.nearest_by(0.0, 0.0, |i| (i != 0).then(|| positions[i * 2].powi(2) + positions[i * 2 + 1].powi(2)));
+        assert_eq!(found, Some((1, 10_000.0)));
+    }
+
+    #[test]
+    fn rebuild_forgets_previous_entries() {
+        let mut grid = EnemyGrid::new(128.0);
+        grid.rebuild(&[10.0, 10.0, 20.0, 20.0, 500.0, 500.0]);
+        grid.rebuild(&[500.0, 500.0]);
+        let mut out = vec![7];
+        grid.query_neighbors_into(10.0, 10.0, &mut out);
+        assert!(out.is_empty());
+        assert_eq!(grid.query_neighbors(500.0, 500.0), vec![0]);
 
-        neighbors
+        grid.set_cell_size(256.0);
+        grid.rebuild(&[10.0, 10.0, 300.0, 10.0]);
+        assert_eq!(grid.query_neighbors(10.0, 10.0), vec![0, 1]);
     }
 }
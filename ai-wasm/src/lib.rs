@@ -3,12 +3,28 @@ mod types;
 mod grid;
 mod enemy_ai;
 mod module_ai;
+mod events;
+mod split;
+mod aggression;
+mod config;
+mod archetype;
+mod steering;
+mod fsm;
+mod formation;
+mod bodies;
+mod flock;
+mod squad;
+mod flowfield;
 
+use std::cell::RefCell;
 use wasm_bindgen::prelude::*;
 use types::*;
 use grid::*;
 use enemy_ai::*;
 use module_ai::*;
+use events::*;
+use flock::FlockSnapshot;
+use squad::SquadPlan;
 
 // Initialize WASM panic hook for better error messages
 #[wasm_bindgen(start)]
@@ -17,110 +33,754 @@ pub fn init() {
     console_error_panic_hook::set_once();
 }
 
+// ========== RANDOM SEED ==========
+// Seeding makes update_enemies / particle output reproducible run to run.
+// The value returned by get_rng_state can be passed back to set_seed to resume.
+#[wasm_bindgen]
+pub fn set_seed(seed: u32) {
+    rng::seed(seed);
+}
+
+#[wasm_bindgen]
+pub fn get_rng_state() -> u32 {
+    rng::state()
+}
+
 // ========== ENEMY GRID SYSTEM ==========
-static mut ENEMY_GRID: Option<EnemyGrid> = None;
+thread_local! {
+    static ENEMY_GRID: RefCell<Option<EnemyGrid>> = const { RefCell::new(None) };
+}
 
 #[wasm_bindgen]
 pub fn rebuild_enemy_grid(positions: &[f64]) {
-    unsafe {
-        let grid = ENEMY_GRID.get_or_insert_with(|| EnemyGrid::new(128.0));
+    ENEMY_GRID.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        let grid = cell.get_or_insert_with(|| EnemyGrid::new(128.0));
         grid.rebuild(positions);
-    }
+    });
 }
 
 #[wasm_bindgen]
 pub fn query_enemy_neighbors(x: f64, y: f64) -> Vec<usize> {
-    unsafe {
-        ENEMY_GRID
+    ENEMY_GRID.with(|cell| {
+        cell.borrow()
             .as_ref()
             .map(|grid| grid.query_neighbors(x, y))
             .unwrap_or_default()
-    }
+    })
 }
 
 // ========== ENEMY AI UPDATE ==========
+// Start-of-tick squad plan and flock, rebuilt in place every tick
+#[derive(Default)]
+struct TickPlan {
+    squads: SquadPlan,
+    flock: FlockSnapshot,
+}
+
+thread_local! {
+    static TICK_PLAN: RefCell<TickPlan> = RefCell::new(TickPlan::default());
+}
+
+// Update enemies in place; earlier enemies' new state is visible to later ones (same as the JS loop).
+// Squads re-form and flockers find neighbours from the starting positions.
+// Body overlaps are resolved once everyone has moved.
+fn tick_enemies(enemy_data: &mut [f64], ctx: &TickContext, events: &mut Vec<AiEvent>) {
+    let num_enemies = enemy_data.len() / ENEMY_STRIDE;
+
+    TICK_PLAN.with(|cell| {
+        let TickPlan { squads, flock } = &mut *cell.borrow_mut();
+        squads.rebuild(enemy_data, events);
+        let flock = flock.rebuild(enemy_data, ctx, &config::flock_config()).then_some(&*flock);
+
+        for i in 0..num_enemies {
+            let offset = i * ENEMY_STRIDE;
+            let first_event = events.len();
+            let enemy = EnemyState::from_slice(&enemy_data[offset..offset + ENEMY_STRIDE]);
+            let updated = update_single_enemy(i, enemy, ctx, enemy_data, flock, squads, events);
+            updated.write_to_slice(&mut enemy_data[offset..offset + ENEMY_STRIDE]);
+            apply_area_damage(enemy_data, events, first_event);
+        }
+    });
+
+    bodies::resolve_bodies(enemy_data, ctx, events);
+}
+
+// Apply damage this enemy dealt to others (exploder blasts) directly to the buffer
+fn apply_area_damage(enemy_data: &mut [f64], events: &mut Vec<AiEvent>, first_event: usize) {
+    for k in first_event..events.len() {
+        let AiEvent::AreaDamage { target, amount, .. } = events[k] else {
+            continue;
+        };
+        let offset = target * ENEMY_STRIDE;
+        let was_alive = enemy_data[offset + 8] > 0.0;
+        enemy_data[offset + 8] -= amount;
+        if was_alive && enemy_data[offset + 8] <= 0.0 {
+            events.push(AiEvent::Killed {
+                index: target,
+                x: enemy_data[offset],
+                y: enemy_data[offset + 1],
+                faction: faction::from_f64(enemy_data[offset + 5]),
+            });
+        }
+    }
+}
+
+// Returns { enemies: [ENEMY_STRIDE-float states], events: Float64Array } (event layout in events.rs)
+// Allocates per call; AiState is the zero-copy path for the game loop
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
 pub fn update_enemies(
     enemy_data: &[f64],
     ship_x: f64,
     ship_y: f64,
+    ship_vx: f64,
+    ship_vy: f64,
     ship_radius: f64,
     module_data: &[f64],
     projectile_data: &[f64],
     canvas_width: f64,
     canvas_height: f64,
     shield_active: bool,
-) -> js_sys::Array {
-    let num_enemies = enemy_data.len() / ENEMY_STRIDE;
-    let mut results = js_sys::Array::new();
+) -> js_sys::Object {
+    let mut enemies = enemy_data.to_vec();
+    let mut events = Vec::new();
+    archetype::with_archetypes(|archetypes| {
+        flowfield::with_field(ship_x, ship_y, canvas_width, canvas_height, |flow_field| {
+            let ctx = TickContext {
+                ship_x,
+                ship_y,
+                ship_vx,
+                ship_vy,
+                ship_radius,
+                module_data,
+                projectile_data,
+                canvas_width,
+                canvas_height,
+                shield_active,
+                archetypes,
+                hostility: faction::hostility(),
+                flow_field,
+            };
+            tick_enemies(&mut enemies, &ctx, &mut events);
+        });
+    });
+
+    let results = js_sys::Array::new();
+    for chunk in enemies.chunks_exact(ENEMY_STRIDE) {
+        results.push(&EnemyState::from_slice(chunk).to_js_value());
+    }
 
-    for i in 0..num_enemies {
-        let offset = i * ENEMY_STRIDE;
-        let enemy = EnemyState::from_slice(&enemy_data[offset..offset + ENEMY_STRIDE]);
+    let mut event_buffer = Vec::new();
+    write_events(&events, &mut event_buffer);
 
-        let updated = update_single_enemy(
-            enemy,
-            ship_x,
-            ship_y,
-            ship_radius,
-            module_data,
-            projectile_data,
-            enemy_data,
-            canvas_width,
-            canvas_height,
-            shield_active,
-        );
+    let output = js_sys::Object::new();
+    js_sys::Reflect::set(&output, &"enemies".into(), &results).unwrap();
+    js_sys::Reflect::set(
+        &output,
+        &"events".into(),
+        &js_sys::Float64Array::from(event_buffer.as_slice()),
+    )
+    .unwrap();
+    output
+}
+
+// Persistent AI state - JS writes enemies/modules/projectiles straight into WASM memory,
+// the tick updates the enemy buffer in place and leaves events in a flat buffer
+#[wasm_bindgen]
+pub struct AiState {
+    enemy_buffer: Vec<f64>,
+    mod_buffer: Vec<f64>,
+    proj_buffer: Vec<f64>,
 
-        results.push(&updated.to_js_value());
+    // Results
+    events: Vec<AiEvent>,
+    event_buffer: Vec<f64>,
+}
+
+impl Default for AiState {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    results
+#[wasm_bindgen]
+impl AiState {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> AiState {
+        AiState {
+            enemy_buffer: Vec::with_capacity(64 * ENEMY_STRIDE),
+            mod_buffer: Vec::with_capacity(30),
+            proj_buffer: Vec::with_capacity(2000),
+            events: Vec::with_capacity(128),
+            event_buffer: Vec::with_capacity(128 * AI_EVENT_STRIDE),
+        }
+    }
+
+    // Buffer management methods
+    pub fn get_enemy_buffer_ptr(&self) -> *const f64 { self.enemy_buffer.as_ptr() }
+    pub fn ensure_enemy_buffer_size(&mut self, size: usize) {
+        if self.enemy_buffer.len() < size { self.enemy_buffer.resize(size, 0.0); }
+    }
+
+    pub fn get_mod_buffer_ptr(&self) -> *const f64 { self.mod_buffer.as_ptr() }
+    pub fn ensure_mod_buffer_size(&mut self, size: usize) {
+        if self.mod_buffer.len() < size { self.mod_buffer.resize(size, 0.0); }
+    }
+
+    pub fn get_proj_buffer_ptr(&self) -> *const f64 { self.proj_buffer.as_ptr() }
+    pub fn ensure_proj_buffer_size(&mut self, size: usize) {
+        if self.proj_buffer.len() < size { self.proj_buffer.resize(size, 0.0); }
+    }
+
+    // Result accessors
+    pub fn get_event_buffer_ptr(&self) -> *const f64 { self.event_buffer.as_ptr() }
+    pub fn get_event_buffer_len(&self) -> usize { self.event_buffer.len() }
+    pub fn get_event_count(&self) -> usize { self.events.len() }
+
+    // Main update - called once per frame from JavaScript
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        num_enemies: usize,
+        num_modules: usize,
+        num_projs: usize,
+        ship_x: f64,
+        ship_y: f64,
+        ship_vx: f64,
+        ship_vy: f64,
+        ship_radius: f64,
+        canvas_width: f64,
+        canvas_height: f64,
+        shield_active: bool,
+    ) {
+        self.events.clear();
+
+        archetype::with_archetypes(|archetypes| {
+            flowfield::with_field(ship_x, ship_y, canvas_width, canvas_height, |flow_field| {
+                let ctx = TickContext {
+                    ship_x,
+                    ship_y,
+                    ship_vx,
+                    ship_vy,
+                    ship_radius,
+                    module_data: &self.mod_buffer[..num_modules * 3],
+                    projectile_data: &self.proj_buffer[..num_projs * 5],
+                    canvas_width,
+                    canvas_height,
+                    shield_active,
+                    archetypes,
+                    hostility: faction::hostility(),
+                    flow_field,
+                };
+
+                tick_enemies(
+                    &mut self.enemy_buffer[..num_enemies * ENEMY_STRIDE],
+                    &ctx,
+                    &mut self.events,
+                );
+            });
+        });
+
+        write_events(&self.events, &mut self.event_buffer);
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_ai_event_stride() -> usize {
+    AI_EVENT_STRIDE
+}
+
+// ========== AGGRESSION ==========
+// Global multiplier on every enemy's own aggression (thrust, charge chance, dodge, fire rate).
+// Clamped to [0.25, 3]; errors for NaN or infinity.
+#[wasm_bindgen]
+pub fn set_global_aggression(value: f64) -> Result<(), JsValue> {
+    aggression::set_global(value).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn get_global_aggression() -> f64 {
+    aggression::global()
+}
+
+// Ramp difficulty by wave number; returns the multiplier now in effect
+#[wasm_bindgen]
+pub fn set_wave_aggression(wave: u32) -> f64 {
+    aggression::set_global(aggression::for_wave(wave)).expect("wave aggression ramp is finite");
+    aggression::global()
+}
+
+// ========== ARCHETYPES ==========
+// Replace the enemy archetype list with a JSON array (schema: src/archetypes.json);
+// type ids are array positions. Returns the number of archetypes loaded.
+#[wasm_bindgen]
+pub fn load_archetypes(json: &str) -> Result<usize, JsValue> {
+    archetype::load(json).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn reset_archetypes() {
+    archetype::reset();
+}
+
+#[wasm_bindgen]
+pub fn get_archetypes() -> JsValue {
+    archetype::with_archetypes(|list| serde_wasm_bindgen::to_value(list).unwrap())
+}
+
+// Type id for an archetype name, -1 if none is loaded under that name
+#[wasm_bindgen]
+pub fn get_archetype_id(name: &str) -> i32 {
+    archetype::find(name).map_or(-1, |id| id as i32)
+}
+
+// Turn boids flocking on or off for an archetype (circle movers only).
+// Returns false for an unknown id.
+#[wasm_bindgen]
+pub fn set_archetype_flocking(type_id: u32, enabled: bool) -> bool {
+    archetype::set_flocking(type_id as usize, enabled)
+}
+
+// ========== SPLITTING ==========
+// Override an archetype's split rule; children = 0 disables it.
+// Children are created while split_level < max_depth.
+// Errors for an unknown id, children > 8 or max_depth > 4.
+#[wasm_bindgen]
+pub fn set_split_rule(type_id: u32, children: u32, max_depth: u32) -> Result<(), JsValue> {
+    archetype::set_split_rule(type_id as usize, split::SplitRule { children, max_depth })
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+// Call when an enemy dies; returns child records (ENEMY_STRIDE floats each) for the host to insert
+#[wasm_bindgen]
+pub fn split_enemy(enemy: &[f64]) -> Vec<f64> {
+    if enemy.len() < ENEMY_STRIDE {
+        return Vec::new();
+    }
+
+    let parent = EnemyState::from_slice(&enemy[..ENEMY_STRIDE]);
+    let children = split::split_children(&parent);
+
+    let mut data = vec![0.0; children.len() * ENEMY_STRIDE];
+    for (child, chunk) in children.iter().zip(data.chunks_exact_mut(ENEMY_STRIDE)) {
+        child.write_to_slice(chunk);
+    }
+    data
 }
 
 // ========== MODULE AI ==========
+// Module targeting indexes the enemy buffer it is handed. ENEMY_GRID is not searched:
+// it holds whatever positions JS passed last, which may have moved since.
+thread_local! {
+    static TARGET_GRID: RefCell<EnemyGrid> = RefCell::new(EnemyGrid::new(128.0));
+}
+
+fn with_target_grid<R>(enemy_data: &[f64], f: impl FnOnce(&EnemyGrid) -> R) -> R {
+    let positions: Vec<f64> = enemy_data
+        .chunks_exact(ENEMY_STRIDE)
+        .flat_map(|record| [record[0], record[1]])
+        .collect();
+    TARGET_GRID.with(|cell| {
+        let mut grid = cell.borrow_mut();
+        grid.rebuild(&positions);
+        f(&grid)
+    })
+}
+
+// strategy: 0 = nearest, 1 = threat, 2 = lowest HP, 3 = spread (see TargetStrategy)
+// bullet_damage sizes how many modules focus one enemy
 #[wasm_bindgen]
 pub fn find_module_targets(
     module_data: &[f64],
     enemy_data: &[f64],
+    strategy: u32,
+    bullet_damage: f64,
+    ship_x: f64,
+    ship_y: f64,
 ) -> js_sys::Array {
-    let num_modules = module_data.len() / 3;
-    let mut results = js_sys::Array::new();
-
-    for i in 0..num_modules {
-        let offset = i * 3;
-        let mx = module_data[offset];
-        let my = module_data[offset + 1];
+    let targets = with_target_grid(enemy_data, |grid| {
+        assign_targets(module_data, enemy_data, strategy.into(), bullet_damage, ship_x, ship_y, Some(grid))
+    });
 
-        let target = find_nearest_enemy_target(mx, my, enemy_data);
+    let results = js_sys::Array::new();
+    for target in &targets {
         results.push(&target.to_js_value());
     }
-
     results
 }
 
+// Lead each module's assigned target with an exact intercept (arc-aware for circling enemies).
+// Returns FIRE_SOLUTION_STRIDE floats per module: [target_index, angle, time_to_hit]
+#[wasm_bindgen]
+pub fn solve_module_fire(
+    module_data: &[f64],
+    enemy_data: &[f64],
+    bullet_speed: f64,
+    bullet_damage: f64,
+    strategy: u32,
+    ship_x: f64,
+    ship_y: f64,
+) -> Vec<f64> {
+    let targets = with_target_grid(enemy_data, |grid| {
+        assign_targets(module_data, enemy_data, strategy.into(), bullet_damage, ship_x, ship_y, Some(grid))
+    });
+    solve_fire(module_data, enemy_data, bullet_speed, &targets)
+}
+
+#[wasm_bindgen]
+pub fn get_fire_solution_stride() -> usize {
+    FIRE_SOLUTION_STRIDE
+}
+
+// Stateful module combat: owns per-module cooldowns and weapon settings and returns the
+// shots to spawn each frame (MODULE_SHOT_STRIDE floats each, see module_ai.rs)
+#[wasm_bindgen]
+pub struct ModuleController {
+    config: ModuleWeaponConfig,
+    cooldowns: Vec<f64>,
+    shot_buffer: Vec<f64>,
+}
+
+impl Default for ModuleController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl ModuleController {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ModuleController {
+        ModuleController {
+            config: ModuleWeaponConfig::default(),
+            cooldowns: Vec::with_capacity(8),
+            shot_buffer: Vec::with_capacity(8 * MODULE_SHOT_STRIDE),
+        }
+    }
+
+    // Modules are identified by position in module_data; added modules start ready to fire
+    pub fn update(
+        &mut self,
+        module_data: &[f64],
+        enemy_data: &[f64],
+        ship_x: f64,
+        ship_y: f64,
+        firing: bool,
+    ) -> usize {
+        self.cooldowns.resize(module_data.len() / 3, 0.0);
+        self.shot_buffer.clear();
+
+        with_target_grid(enemy_data, |grid| {
+            fire_modules(
+                &self.config,
+                &mut self.cooldowns,
+                module_data,
+                enemy_data,
+                ship_x,
+                ship_y,
+                firing,
+                Some(grid),
+                &mut self.shot_buffer,
+            );
+        });
+
+        self.shot_buffer.len() / MODULE_SHOT_STRIDE
+    }
+
+    pub fn get_shot_buffer_ptr(&self) -> *const f64 { self.shot_buffer.as_ptr() }
+    pub fn get_shot_buffer_len(&self) -> usize { self.shot_buffer.len() }
+
+    pub fn get_config(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.config).unwrap()
+    }
+
+    // Accepts a full or partial { COOLDOWN, MIN_RANGE, ... } object
+    pub fn set_config(&mut self, obj: JsValue) -> Result<(), JsValue> {
+        let merged = js_sys::Object::assign(&self.get_config().into(), &obj.into());
+        let config: ModuleWeaponConfig = serde_wasm_bindgen::from_value(merged.into())
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        config.validate().map_err(|e| JsValue::from_str(&e))?;
+        self.config = config;
+        Ok(())
+    }
+
+    pub fn reset_cooldowns(&mut self) {
+        self.cooldowns.iter_mut().for_each(|c| *c = 0.0);
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_module_shot_stride() -> usize {
+    MODULE_SHOT_STRIDE
+}
+
+// ========== MODULE FORMATION ==========
+// Owns module placement: docked orbit, deployed formation and recall. update() moves the
+// modules in module_data and returns the module count; the buffer holds
+// FORMATION_STRIDE floats per module ([target_x, target_y, x, y]).
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct ModuleFormation {
+    solver: formation::FormationSolver,
+    buffer: Vec<f64>,
+}
+
+#[wasm_bindgen]
+impl ModuleFormation {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ModuleFormation {
+        ModuleFormation::default()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        module_data: &[f64],
+        enemy_data: &[f64],
+        ship_x: f64,
+        ship_y: f64,
+        ship_vx: f64,
+        ship_vy: f64,
+        deployed: bool,
+    ) -> usize {
+        let mut modules = module_data.to_vec();
+        self.buffer.clear();
+
+        with_target_grid(enemy_data, |grid| {
+            self.solver.update(
+                &mut modules,
+                enemy_data,
+                ship_x,
+                ship_y,
+                ship_vx,
+                ship_vy,
+                deployed,
+                Some(grid),
+                &mut self.buffer,
+            );
+        });
+
+        self.buffer.len() / formation::FORMATION_STRIDE
+    }
+
+    pub fn get_buffer_ptr(&self) -> *const f64 { self.buffer.as_ptr() }
+    pub fn get_buffer_len(&self) -> usize { self.buffer.len() }
+
+    // 0 ring, 1 line, 2 wedge, 3 escort; takes effect while deployed
+    pub fn set_formation(&mut self, formation: u32) {
+        self.solver.formation = formation.into();
+    }
+
+    pub fn get_formation(&self) -> u32 {
+        self.solver.formation as u32
+    }
+
+    pub fn get_config(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.solver.config).unwrap()
+    }
+
+    // Accepts a full or partial { LERP, MAX_SPEED, RING_RADIUS, ... } object
+    pub fn set_config(&mut self, obj: JsValue) -> Result<(), JsValue> {
+        let merged = js_sys::Object::assign(&self.get_config().into(), &obj.into());
+        let config: formation::FormationConfig = serde_wasm_bindgen::from_value(merged.into())
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        config.validate().map_err(|e| JsValue::from_str(&e))?;
+        self.solver.config = config;
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_formation_stride() -> usize {
+    formation::FORMATION_STRIDE
+}
+
+// ========== FACTIONS ==========
+// Relation ids: 0 allied, 1 neutral (hit but never targeted), 2 hostile.
+// Read-only here apart from set_faction_matrix, which only setFactionRelation /
+// resetFactionMatrix in gameAI.js call; they push the same matrix to collision-wasm.
+
+// Row-major MAX_FACTIONS x MAX_FACTIONS relation ids
+#[wasm_bindgen]
+pub fn get_faction_matrix() -> Vec<f64> {
+    faction::hostility().to_flat()
+}
+
+#[wasm_bindgen]
+pub fn set_faction_matrix(values: &[f64]) -> Result<(), JsValue> {
+    let hostility = faction::Hostility::from_flat(values).map_err(|e| JsValue::from_str(&e))?;
+    faction::set_hostility(hostility);
+    Ok(())
+}
+
 // ========== RAMMER CONFIGURATION EXPORT ==========
 #[wasm_bindgen]
-pub fn get_rammer_config() -> js_sys::Object {
-    let config = js_sys::Object::new();
-
-    js_sys::Reflect::set(&config, &"BASE_MAX".into(), &JsValue::from_f64(9.0)).unwrap();
-    js_sys::Reflect::set(&config, &"BOOST_MAX".into(), &JsValue::from_f64(28.0)).unwrap();
-    js_sys::Reflect::set(&config, &"REST".into(), &JsValue::from_f64(1.6)).unwrap();
-    js_sys::Reflect::set(&config, &"DAMP".into(), &JsValue::from_f64(0.985)).unwrap();
-    js_sys::Reflect::set(&config, &"BOOST_FRAMES".into(), &JsValue::from_f64(14.0)).unwrap();
-    js_sys::Reflect::set(&config, &"HIT_CD".into(), &JsValue::from_f64(4.0)).unwrap();
-    js_sys::Reflect::set(&config, &"STEER".into(), &JsValue::from_f64(0.5)).unwrap();
-    js_sys::Reflect::set(&config, &"THRUST".into(), &JsValue::from_f64(1.2)).unwrap();
-    js_sys::Reflect::set(&config, &"CHARGE_DIST".into(), &JsValue::from_f64(300.0)).unwrap();
-    js_sys::Reflect::set(&config, &"CHARGE_PROB".into(), &JsValue::from_f64(0.02)).unwrap();
-    js_sys::Reflect::set(&config, &"CHARGE_FRAMES".into(), &JsValue::from_f64(26.0)).unwrap();
-    js_sys::Reflect::set(&config, &"CHARGE_SPEED_BONUS".into(), &JsValue::from_f64(6.0)).unwrap();
-    js_sys::Reflect::set(&config, &"DODGE_DIST".into(), &JsValue::from_f64(120.0)).unwrap();
-    js_sys::Reflect::set(&config, &"DODGE_FORCE".into(), &JsValue::from_f64(2.2)).unwrap();
-    js_sys::Reflect::set(&config, &"ORBIT_BREAK_RADIUS".into(), &JsValue::from_f64(140.0)).unwrap();
-    js_sys::Reflect::set(&config, &"ORBIT_TANGENT_DAMP".into(), &JsValue::from_f64(0.9)).unwrap();
-    js_sys::Reflect::set(&config, &"CLOSE_STEER".into(), &JsValue::from_f64(0.75)).unwrap();
-    js_sys::Reflect::set(&config, &"MIN_FWD".into(), &JsValue::from_f64(0.8)).unwrap();
-
-    config
+pub fn get_rammer_config() -> JsValue {
+    serde_wasm_bindgen::to_value(&config::rammer_config()).unwrap()
+}
+
+// Accepts a full or partial { BASE_MAX, THRUST, ... } object; missing keys keep their current value
+#[wasm_bindgen]
+pub fn set_rammer_config(obj: JsValue) -> Result<(), JsValue> {
+    let merged = js_sys::Object::assign(&get_rammer_config().into(), &obj.into());
+    let config: RammerConfig = serde_wasm_bindgen::from_value(merged.into())
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    config::set_rammer_config(config).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn reset_rammer_config() {
+    config::reset_rammer_config();
+}
+
+// ========== FLOCK CONFIGURATION EXPORT ==========
+#[wasm_bindgen]
+pub fn get_flock_config() -> JsValue {
+    serde_wasm_bindgen::to_value(&config::flock_config()).unwrap()
+}
+
+// Accepts a full or partial { NEIGHBOR_RADIUS, ALIGNMENT, ... } object; missing keys keep their current value
+#[wasm_bindgen]
+pub fn set_flock_config(obj: JsValue) -> Result<(), JsValue> {
+    let merged = js_sys::Object::assign(&get_flock_config().into(), &obj.into());
+    let config: flock::FlockConfig = serde_wasm_bindgen::from_value(merged.into())
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    config::set_flock_config(config).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn reset_flock_config() {
+    config::reset_flock_config();
+}
+
+// ========== SQUADS ==========
+// Membership is set by the host in the enemy record: squad id (> 0), slot (0 = leader), shape.
+// Returns [dx, dy] per slot for `members` enemies (slot 0 is the leader at 0, 0), rotated to
+// face `heading`, so waves can spawn already in formation.
+#[wasm_bindgen]
+pub fn get_squad_slot_offsets(shape: u32, members: usize, heading: f64) -> Vec<f64> {
+    squad::slot_offsets(squad::SquadShape::from(shape as f64), members, heading, config::squad_config().spacing)
+}
+
+#[wasm_bindgen]
+pub fn get_squad_config() -> JsValue {
+    serde_wasm_bindgen::to_value(&config::squad_config()).unwrap()
+}
+
+// Accepts a full or partial { SPACING, ENGAGE_RADIUS, CATCH_UP, SLOW_RADIUS } object
+#[wasm_bindgen]
+pub fn set_squad_config(obj: JsValue) -> Result<(), JsValue> {
+    let merged = js_sys::Object::assign(&get_squad_config().into(), &obj.into());
+    let config: squad::SquadConfig = serde_wasm_bindgen::from_value(merged.into())
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    config::set_squad_config(config).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn reset_squad_config() {
+    config::reset_squad_config();
+}
+
+// ========== FLOW FIELD ==========
+// Regions are world-space rectangles; later ones override earlier ones where they overlap.
+// Ship-hunting chasers and exploders path around blocked cells and prefer cheap ones.
+#[wasm_bindgen]
+pub fn add_flow_obstacle(x: f64, y: f64, width: f64, height: f64) -> Result<(), JsValue> {
+    flowfield::with_field_mut(|field| field.add_blocked(x, y, width, height)).map_err(|e| JsValue::from_str(&e))
+}
+
+// cost >= 1 multiplies the price of crossing the area (1 = open ground, e.g. to clear part of an obstacle)
+#[wasm_bindgen]
+pub fn add_flow_cost(x: f64, y: f64, width: f64, height: f64, cost: f64) -> Result<(), JsValue> {
+    flowfield::with_field_mut(|field| field.add_cost(x, y, width, height, cost)).map_err(|e| JsValue::from_str(&e))
+}
+
+#[wasm_bindgen]
+pub fn clear_flow_field() {
+    flowfield::with_field_mut(|field| field.clear());
+}
+
+// Grid resolution in pixels (default 40); regions are kept
+#[wasm_bindgen]
+pub fn set_flow_cell_size(cell_size: f64) -> Result<(), JsValue> {
+    flowfield::set_cell_size(cell_size).map_err(|e| JsValue::from_str(&e))
+}
+
+// [dx, dy] heading towards the ship from (x, y) as of the last tick; empty when the field
+// is not in use or the ship cannot be reached from there
+#[wasm_bindgen]
+pub fn get_flow_direction(x: f64, y: f64) -> Vec<f64> {
+    flowfield::with_field_mut(|field| {
+        if !field.is_active() {
+            return Vec::new();
+        }
+        field.sample(x, y).map_or_else(Vec::new, |dir| vec![dir.x, dir.y])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use archetype::ArchetypeId;
+    use faction::FactionId;
+
+    // One of each archetype, a squad of basics and a zombie so every random draw site is hit
+    fn arena() -> Vec<f64> {
+        let spawns: [(f64, f64, ArchetypeId, FactionId, f64); 8] = [
+            (100.0, 100.0, 0, faction::ENEMY, 0.0),
+            (700.0, 120.0, 1, faction::ENEMY, 0.0),
+            (650.0, 500.0, 2, faction::ENEMY, 0.0),
+            (150.0, 520.0, 3, faction::ENEMY, 0.0),
+            (300.0, 80.0, 0, faction::ENEMY, 1.0),
+            (330.0, 90.0, 0, faction::ENEMY, 1.0),
+            (360.0, 100.0, 0, faction::ENEMY, 1.0),
+            (420.0, 420.0, 2, faction::ZOMBIE, 0.0),
+        ];
+        let mut data = Vec::new();
+        for (x, y, kind, side, squad) in spawns {
+            let mut record = [0.0; ENEMY_STRIDE];
+            record[0] = x;
+            record[1] = y;
+            record[4] = kind as f64;
+            record[5] = side as f64;
+            record[7] = 15.0;
+            record[8] = 30.0;
+            record[9] = 30.0;
+            record[16] = 1.0;
+            record[21] = 1.0;
+            record[24] = squad;
+            data.extend_from_slice(&record);
+        }
+        data
+    }
+
+    fn run(seed: u32, ticks: usize) -> (Vec<f64>, Vec<AiEvent>) {
+        rng::seed(seed);
+        let mut enemies = arena();
+        let mut events = Vec::new();
+        archetype::with_archetypes(|archetypes| {
+            let ctx = TickContext { ship_vx: 1.5, ship_vy: -0.5, ..TickContext::for_test(archetypes) };
+            for _ in 0..ticks {
+                tick_enemies(&mut enemies, &ctx, &mut events);
+            }
+        });
+        (enemies, events)
+    }
+
+    #[test]
+    fn same_seed_replays_identically() {
+        let (first_enemies, first_events) = run(1234, 240);
+        let (second_enemies, second_events) = run(1234, 240);
+        assert!(!first_events.is_empty());
+        assert_eq!(first_enemies, second_enemies);
+        assert_eq!(first_events, second_events);
+    }
+
+    #[test]
+    fn different_seeds_diverge() {
+        let (first_enemies, _) = run(1, 240);
+        let (second_enemies, _) = run(2, 240);
+        assert_ne!(first_enemies, second_enemies);
+    }
 }
@@ -0,0 +1,118 @@
+// Splitting enemies - a dying enemy breaks into smaller children
+use serde::{Serialize, Deserialize};
+
+use crate::archetype;
+use crate::fsm::AiMode;
+use rng::random;
+use crate::types::*;
+
+pub struct SplitConfig;
+
+impl SplitConfig {
+    pub const RADIUS_SCALE: f64 = 0.7;
+    pub const HP_SCALE: f64 = 0.5;
+    pub const EJECT_SPEED: f64 = 3.0;
+}
+
+// Per archetype ("split" in archetypes.json); children == 0 disables splitting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SplitRule {
+    pub children: u32,
+    pub max_depth: u32,
+}
+
+// Children spawned when `parent` dies, in its faction; empty for zombies or once max depth is reached
+pub fn split_children(parent: &EnemyState) -> Vec<EnemyState> {
+    let rule = archetype::split_rule(parent.enemy_type);
+    if parent.faction == faction::ZOMBIE || rule.children == 0 || parent.split_level >= rule.max_depth as f64 {
+        return Vec::new();
+    }
+
+    let count = rule.children as usize;
+    let radius = parent.radius * SplitConfig::RADIUS_SCALE;
+    let max_hp = parent.max_hp * SplitConfig::HP_SCALE;
+    let base_angle = random() * TWO_PI;
+
+    (0..count)
+        .map(|k| {
+            let angle = base_angle + TWO_PI * k as f64 / count as f64;
+            let (sin, cos) = angle.sin_cos();
+            EnemyState {
+                x: parent.x + cos * radius,
+                y: parent.y + sin * radius,
+                vx: cos * SplitConfig::EJECT_SPEED,
+                vy: sin * SplitConfig::EJECT_SPEED,
+                radius,
+                hp: max_hp,
+                max_hp,
+                shoot_cooldown: 0.0,
+                angle,
+                bounce_boost_frames: 0.0,
+                hit_cooldown: 0.0,
+                charge_cooldown: 0.0,
+                charge_frames: 0.0,
+                pulse_phase: 0.0,
+                zombie_lifetime: 0.0,
+                split_level: parent.split_level + 1.0,
+                ai_mode: AiMode::Cruise,
+                state_frames: 0.0,
+                squad: 0,
+                squad_slot: 0,
+                ..parent.clone()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use faction::FactionId;
+
+    fn parent(level: f64, owner: FactionId) -> EnemyState {
+        let mut record = [0.0; ENEMY_STRIDE];
+        record[0] = 300.0;
+        record[1] = 200.0;
+        record[5] = owner as f64;
+        record[7] = 20.0;
+        record[9] = 40.0;
+        record[20] = level;
+        EnemyState::from_slice(&record)
+    }
+
+    #[test]
+    fn children_are_smaller_and_one_level_deeper() {
+        archetype::set_split_rule(0, SplitRule { children: 3, max_depth: 2 }).unwrap();
+        let children = split_children(&parent(1.0, faction::ENEMY));
+        archetype::reset();
+
+        assert_eq!(children.len(), 3);
+        for child in &children {
+            assert_eq!(child.split_level, 2.0);
+            assert_eq!(child.radius, 20.0 * SplitConfig::RADIUS_SCALE);
+            assert_eq!((child.hp, child.max_hp), (20.0, 20.0));
+            assert_eq!(child.faction, faction::ENEMY);
+            assert!((hypot(child.vx, child.vy) - SplitConfig::EJECT_SPEED).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn no_children_at_max_depth_or_for_zombies() {
+        archetype::set_split_rule(0, SplitRule { children: 3, max_depth: 2 }).unwrap();
+        let deepest = split_children(&parent(2.0, faction::ENEMY));
+        let zombie = split_children(&parent(0.0, faction::ZOMBIE));
+        archetype::reset();
+
+        assert!(deepest.is_empty());
+        assert!(zombie.is_empty());
+    }
+
+    #[test]
+    fn out_of_range_rules_are_rejected() {
+        assert!(archetype::set_split_rule(0, SplitRule { children: 9, max_depth: 1 }).is_err());
+        assert!(archetype::set_split_rule(0, SplitRule { children: 2, max_depth: 5 }).is_err());
+        assert!(archetype::set_split_rule(usize::MAX, SplitRule::default()).is_err());
+        assert_eq!(archetype::split_rule(0), SplitRule::default());
+    }
+}
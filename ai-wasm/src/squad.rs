@@ -0,0 +1,277 @@
+// Squads - wave groups that fly in formation behind a leader
+// Membership lives in the enemy record (squad id, slot, shape at offsets 24-26); slot 0
+// is the leader, which moves with its archetype's own movement. Every other member steers
+// to its slot around the leader until it engages, then leaves the squad and fights alone.
+// Slots are renumbered at the start of each tick, so a dead leader is replaced
+// by the next member and the rest close ranks.
+use serde::{Serialize, Deserialize};
+
+use crate::events::*;
+use crate::steering::*;
+use crate::types::*;
+
+// Member layout around the leader; the frame faces the squad's target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SquadShape {
+    // Circle around the leader
+    Ring = 0,
+    // Two trailing arms with the leader at the tip
+    V = 1,
+    // Abreast of the leader
+    Line = 2,
+    // Two arms curving forward on both flanks
+    Pincer = 3,
+}
+
+impl From<f64> for SquadShape {
+    fn from(v: f64) -> Self {
+        match v as i32 {
+            1 => SquadShape::V,
+            2 => SquadShape::Line,
+            3 => SquadShape::Pincer,
+            _ => SquadShape::Ring,
+        }
+    }
+}
+
+// Runtime tunable (see config.rs)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct SquadConfig {
+    // Gap between neighbouring slots
+    pub spacing: f64,
+    // Members closer than this to the ship (or any hostile, for zombies) break off
+    pub engage_radius: f64,
+    // Member top speed as a multiple of its archetype speed, so stragglers catch up
+    pub catch_up: f64,
+    // Members slow down inside this distance of their slot
+    pub slow_radius: f64,
+}
+
+impl Default for SquadConfig {
+    fn default() -> Self {
+        Self {
+            spacing: 45.0,
+            engage_radius: 220.0,
+            catch_up: 1.6,
+            slow_radius: 40.0,
+        }
+    }
+}
+
+impl SquadConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        fn check(name: &str, value: f64, min: f64, max: f64) -> Result<(), String> {
+            if value.is_finite() && value >= min && value <= max {
+                Ok(())
+            } else {
+                Err(format!("SquadConfig.{} = {} is outside [{}, {}]", name, value, min, max))
+            }
+        }
+
+        check("SPACING", self.spacing, 0.0, 500.0)?;
+        check("ENGAGE_RADIUS", self.engage_radius, 0.0, 5000.0)?;
+        check("CATCH_UP", self.catch_up, 0.1, 10.0)?;
+        check("SLOW_RADIUS", self.slow_radius, 0.0, 1000.0)?;
+        Ok(())
+    }
+}
+
+// Live squads this tick, keyed by squad id
+#[derive(Debug, Clone, Copy)]
+pub struct SquadInfo {
+    pub leader: usize,
+    pub members: usize,
+}
+
+// Ordered by squad id so rebuild() renumbers and emits events in the same order every run.
+// One plan is rebuilt every tick, so its buffers are reused rather than reallocated.
+#[derive(Debug, Clone, Default)]
+pub struct SquadPlan {
+    squads: Vec<(u32, SquadInfo)>,
+    // (squad, slot, index) of every live member, sorted by squad then slot
+    members: Vec<(u32, f64, usize)>,
+}
+
+impl SquadPlan {
+    pub fn get(&self, squad: u32) -> Option<&SquadInfo> {
+        self.squads
+            .binary_search_by_key(&squad, |&(id, _)| id)
+            .ok()
+            .map(|i| &self.squads[i].1)
+    }
+
+    // Group live members by squad, renumber slots 0..n in their current order and promote
+    // a new leader where slot 0 is gone. A squad left with a single member is disbanded.
+    pub fn rebuild(&mut self, enemy_data: &mut [f64], events: &mut Vec<AiEvent>) {
+        self.squads.clear();
+        self.members.clear();
+        for (i, record) in enemy_data.chunks_exact(ENEMY_STRIDE).enumerate() {
+            if record[24] >= 1.0 && record[8] > 0.0 {
+                self.members.push((record[24] as u32, record[25], i));
+            }
+        }
+        self.members
+            .sort_unstable_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+        for members in self.members.chunk_by(|a, b| a.0 == b.0) {
+            let (squad, leader_slot, leader) = members[0];
+            if members.len() < 2 {
+                let offset = leader * ENEMY_STRIDE;
+                enemy_data[offset + 24] = 0.0;
+                enemy_data[offset + 25] = 0.0;
+                continue;
+            }
+
+            for (slot, &(_, _, i)) in members.iter().enumerate() {
+                enemy_data[i * ENEMY_STRIDE + 25] = slot as f64;
+            }
+            if leader_slot != 0.0 {
+                events.push(AiEvent::SquadLeader { index: leader, squad, members: members.len() });
+            }
+            self.squads.push((squad, SquadInfo { leader, members: members.len() }));
+        }
+    }
+}
+
+// Local offset of slot `slot` (1..members) from the leader; x points along the squad heading
+fn slot_offset(shape: SquadShape, slot: usize, members: usize, spacing: f64) -> Vec2 {
+    let rank = slot.div_ceil(2) as f64;
+    let side = if slot % 2 == 1 { -1.0 } else { 1.0 };
+    match shape {
+        SquadShape::Ring => {
+            let others = (members - 1) as f64;
+            let radius = spacing * (1.0 + others / 6.0);
+            Vec2::from_angle(TWO_PI * (slot - 1) as f64 / others) * radius
+        }
+        SquadShape::V => Vec2::new(-rank * spacing, side * rank * spacing),
+        SquadShape::Line => Vec2::new(0.0, side * rank * spacing),
+        SquadShape::Pincer => {
+            let angle = side * (HALF_PI - 0.3 * rank);
+            Vec2::from_angle(angle) * (spacing * (1.5 + 0.5 * rank))
+        }
+    }
+}
+
+fn rotate(v: Vec2, heading: f64) -> Vec2 {
+    let (sin, cos) = heading.sin_cos();
+    Vec2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
+
+// World-space [dx, dy] per slot for a squad of `members` facing `heading`
+pub fn slot_offsets(shape: SquadShape, members: usize, heading: f64, spacing: f64) -> Vec<f64> {
+    let mut offsets = vec![0.0; members * 2];
+    for slot in 1..members {
+        let offset = rotate(slot_offset(shape, slot, members, spacing), heading);
+        offsets[slot * 2] = offset.x;
+        offsets[slot * 2 + 1] = offset.y;
+    }
+    offsets
+}
+
+// Move a member towards its slot. Returns false when it is not flying in formation this
+// frame (leader, loner, or it just engaged) so the caller runs its normal movement.
+pub fn follow(
+    enemy: &mut EnemyState,
+    speed: f64,
+    target: Option<(f64, f64)>,
+    ctx: &TickContext,
+    enemy_data: &[f64],
+    squads: &SquadPlan,
+    cfg: &SquadConfig,
+) -> bool {
+    if enemy.squad == 0 || enemy.squad_slot == 0 {
+        return false;
+    }
+    let Some(info) = squads.get(enemy.squad) else {
+        return false;
+    };
+
+    // Being hurt or getting close to a target breaks formation for good
+    let pos = Vec2::new(enemy.x, enemy.y);
+    let target = target.map(|(x, y)| Vec2::new(x, y));
+    let near_target = target.is_some_and(|t| (t - pos).length() < cfg.engage_radius);
+    if near_target || enemy.hp < enemy.max_hp {
+        enemy.squad = 0;
+        enemy.squad_slot = 0;
+        return false;
+    }
+
+    let leader = &enemy_data[info.leader * ENEMY_STRIDE..(info.leader + 1) * ENEMY_STRIDE];
+    let leader_pos = Vec2::new(leader[0], leader[1]);
+    let heading = match target {
+        Some(t) if t != leader_pos => (t.y - leader_pos.y).atan2(t.x - leader_pos.x),
+        _ => leader[11],
+    };
+
+    let slot = leader_pos + rotate(slot_offset(enemy.squad_shape, enemy.squad_slot, info.members, cfg.spacing), heading);
+    let slot = Vec2::new(
+        clamp(slot.x, enemy.radius, ctx.canvas_width - enemy.radius),
+        clamp(slot.y, enemy.radius, ctx.canvas_height - enemy.radius),
+    );
+
+    let step = arrive(pos, slot, speed * cfg.catch_up, 0.0, cfg.slow_radius);
+    enemy.x += step.x;
+    enemy.y += step.y;
+    enemy.vx = step.x;
+    enemy.vy = step.y;
+    // Face the way the squad faces so a member that breaks off carries on in that direction
+    enemy.angle = heading;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_promotes_leaders_in_squad_order() {
+        // Three squads whose slot 0 leaders are gone, listed out of id order in the buffer
+        let squads = [7.0, 3.0, 5.0, 7.0, 3.0, 5.0];
+        let mut enemy_data = vec![0.0; squads.len() * ENEMY_STRIDE];
+        for (i, (record, squad)) in enemy_data.chunks_exact_mut(ENEMY_STRIDE).zip(squads).enumerate() {
+            record[8] = 10.0;
+            record[24] = squad;
+            record[25] = 1.0 + (i / 3) as f64;
+        }
+
+        let mut events = Vec::new();
+        let mut plan = SquadPlan::default();
+        plan.rebuild(&mut enemy_data, &mut events);
+        assert_eq!(plan.squads.iter().map(|&(id, _)| id).collect::<Vec<_>>(), vec![3, 5, 7]);
+        let leaders: Vec<(usize, u32)> = events
+            .iter()
+            .map(|event| match *event {
+                AiEvent::SquadLeader { index, squad, members } => {
+                    assert_eq!(members, 2);
+                    (index, squad)
+                }
+                _ => panic!("unexpected event {:?}", event),
+            })
+            .collect();
+        assert_eq!(leaders, vec![(1, 3), (2, 5), (0, 7)]);
+    }
+
+    #[test]
+    fn rebuild_replaces_previous_tick() {
+        let mut enemy_data = vec![0.0; 3 * ENEMY_STRIDE];
+        for (i, record) in enemy_data.chunks_exact_mut(ENEMY_STRIDE).enumerate() {
+            record[8] = 10.0;
+            record[24] = 4.0;
+            record[25] = i as f64;
+        }
+
+        let mut events = Vec::new();
+        let mut plan = SquadPlan::default();
+        plan.rebuild(&mut enemy_data, &mut events);
+        assert_eq!(plan.get(4).map(|info| info.members), Some(3));
+
+        // Leader and one member die; the survivor is disbanded and the squad is gone
+        enemy_data[8] = 0.0;
+        enemy_data[ENEMY_STRIDE + 8] = 0.0;
+        plan.rebuild(&mut enemy_data, &mut events);
+        assert!(plan.get(4).is_none());
+        assert_eq!(enemy_data[2 * ENEMY_STRIDE + 24], 0.0);
+        assert!(events.is_empty());
+    }
+}
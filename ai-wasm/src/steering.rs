@@ -0,0 +1,357 @@
+// Steering behaviors - small composable forces for enemy movement
+// Each behavior returns a desired velocity change; callers weight them with SteeringBlend
+// and truncate the result to the enemy's top speed.
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+use rng::random;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+    #[inline]
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    #[inline]
+    pub fn from_angle(angle: f64) -> Self {
+        Self { x: angle.cos(), y: angle.sin() }
+    }
+
+    #[inline]
+    pub fn length(self) -> f64 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+
+    #[inline]
+    pub fn dot(self, other: Vec2) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    // Unit vector, or zero for a zero-length vector
+    #[inline]
+    pub fn normalized(self) -> Vec2 {
+        let len = self.length();
+        if len > 0.0 {
+            self * (1.0 / len)
+        } else {
+            Vec2::ZERO
+        }
+    }
+
+    // Divides by max(len, 1): vectors shorter than 1 keep their length (the JS idiom)
+    #[inline]
+    pub fn soft_normalized(self) -> Vec2 {
+        self * (1.0 / self.length().max(1.0))
+    }
+
+    // Scale down to max_len if longer
+    #[inline]
+    pub fn truncate(self, max_len: f64) -> Vec2 {
+        let len = self.length();
+        if len > max_len {
+            self * (max_len / len)
+        } else {
+            self
+        }
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn add(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    #[inline]
+    fn add_assign(&mut self, rhs: Vec2) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn sub(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn mul(self, rhs: f64) -> Vec2 {
+        Vec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+// Weighted sum of steering forces
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SteeringBlend {
+    force: Vec2,
+}
+
+impl SteeringBlend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn add(mut self, force: Vec2, weight: f64) -> Self {
+        self.force += force * weight;
+        self
+    }
+
+    #[inline]
+    pub fn result(self) -> Vec2 {
+        self.force
+    }
+}
+
+// Full speed straight at the target
+pub fn seek(pos: Vec2, target: Vec2, max_speed: f64) -> Vec2 {
+    (target - pos).normalized() * max_speed
+}
+
+// Full speed straight away from the threat
+pub fn flee(pos: Vec2, threat: Vec2, max_speed: f64) -> Vec2 {
+    -seek(pos, threat, max_speed)
+}
+
+// Seek that stops at stop_radius and ramps down over the slow_radius band outside it
+pub fn arrive(pos: Vec2, target: Vec2, max_speed: f64, stop_radius: f64, slow_radius: f64) -> Vec2 {
+    let offset = target - pos;
+    let dist = offset.length();
+    if dist <= 0.0 || dist <= stop_radius {
+        return Vec2::ZERO;
+    }
+
+    let speed = if dist < stop_radius + slow_radius {
+        max_speed * (dist - stop_radius) / slow_radius
+    } else {
+        max_speed
+    };
+    offset * (speed / dist)
+}
+
+// Straight-line travel time to `other` at max_speed, capped at max_lookahead frames
+fn lookahead(pos: Vec2, other: Vec2, max_speed: f64, max_lookahead: f64) -> f64 {
+    if max_speed <= 0.0 {
+        return max_lookahead;
+    }
+    ((other - pos).length() / max_speed).min(max_lookahead)
+}
+
+// Seek where a moving target will be, looking ahead at most max_lookahead frames
+pub fn pursue(pos: Vec2, target: Vec2, target_vel: Vec2, max_speed: f64, max_lookahead: f64) -> Vec2 {
+    let lookahead = lookahead(pos, target, max_speed, max_lookahead);
+    seek(pos, target + target_vel * lookahead, max_speed)
+}
+
+// Flee from where a moving threat will be
+pub fn evade(pos: Vec2, threat: Vec2, threat_vel: Vec2, max_speed: f64, max_lookahead: f64) -> Vec2 {
+    let lookahead = lookahead(pos, threat, max_speed, max_lookahead);
+    flee(pos, threat + threat_vel * lookahead, max_speed)
+}
+
+// Smallest t >= 0 with |offset + target_vel * t| = speed * t, i.e. when a shot (or body)
+// leaving now at `speed` meets a target at `offset` moving with target_vel. None if it never can.
+pub fn intercept_time(offset: Vec2, target_vel: Vec2, speed: f64) -> Option<f64> {
+    let a = target_vel.dot(target_vel) - speed * speed;
+    let b = 2.0 * offset.dot(target_vel);
+    let c = offset.dot(offset);
+
+    if a.abs() < 1e-9 {
+        // Equal speeds: linear equation b*t + c = 0
+        if b >= 0.0 {
+            return None;
+        }
+        return Some(-c / b);
+    }
+
+    let disc = b * b - 4.0 * a * c;
+    if disc < 0.0 {
+        return None;
+    }
+    let root = disc.sqrt();
+    let t1 = (-b - root) / (2.0 * a);
+    let t2 = (-b + root) / (2.0 * a);
+    let t = match (t1 >= 0.0, t2 >= 0.0) {
+        (true, true) => t1.min(t2),
+        (true, false) => t1,
+        (false, true) => t2,
+        (false, false) => return None,
+    };
+    Some(t)
+}
+
+// Where to aim to meet a moving target, looking at most max_time frames ahead.
+// Without an exact intercept it falls back to leading by the straight-line travel time.
+pub fn intercept_point(pos: Vec2, speed: f64, target: Vec2, target_vel: Vec2, max_time: f64) -> Vec2 {
+    let t = intercept_time(target - pos, target_vel, speed)
+        .unwrap_or_else(|| lookahead(pos, target, speed, max_time))
+        .min(max_time);
+    target + target_vel * t
+}
+
+// Turn the heading by turn_rate plus up to +/- jitter, then move along it.
+// jitter = 0 draws no random numbers, so it keeps the RNG sequence untouched.
+pub fn wander(heading: &mut f64, turn_rate: f64, jitter: f64, speed: f64) -> Vec2 {
+    *heading += turn_rate;
+    if jitter > 0.0 {
+        *heading += (random() * 2.0 - 1.0) * jitter;
+    }
+    Vec2::from_angle(*heading) * speed
+}
+
+// Push away from neighbours inside radius, stronger the closer they are
+pub fn separation(pos: Vec2, neighbors: impl IntoIterator<Item = Vec2>, radius: f64, max_force: f64) -> Vec2 {
+    let mut force = Vec2::ZERO;
+    for other in neighbors {
+        let away = pos - other;
+        let dist = away.length();
+        if dist > 0.0 && dist < radius {
+            force += away * ((radius - dist) / (radius * dist));
+        }
+    }
+    force.truncate(1.0) * max_force
+}
+
+// Moving obstacles (pos, vel) inside detect_radius whose approach along their own
+// heading is below approach_limit are evaded with `force`, looking ahead at most max_lookahead frames
+pub fn avoid_obstacles(
+    pos: Vec2,
+    obstacles: impl IntoIterator<Item = (Vec2, Vec2)>,
+    detect_radius: f64,
+    approach_limit: f64,
+    force: f64,
+    max_lookahead: f64,
+) -> Vec2 {
+    let mut total = Vec2::ZERO;
+    for (obstacle_pos, obstacle_vel) in obstacles {
+        let offset = obstacle_pos - pos;
+        if offset.length() >= detect_radius {
+            continue;
+        }
+        if offset.dot(obstacle_vel.soft_normalized()) < approach_limit {
+            total += evade(pos, obstacle_pos, obstacle_vel, force, max_lookahead);
+        }
+    }
+    total
+}
+
+// Scale the velocity component perpendicular to `normal` (unit) by `damp`
+pub fn damp_tangential(vel: Vec2, normal: Vec2, damp: f64) -> Vec2 {
+    let radial = normal * vel.dot(normal);
+    radial + (vel - radial) * damp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meets(offset: Vec2, target_vel: Vec2, speed: f64, t: f64) -> bool {
+        ((offset + target_vel * t).length() - speed * t).abs() < 1e-6
+    }
+
+    #[test]
+    fn intercept_without_real_root_is_none() {
+        // Faster target crossing sideways: the discriminant is negative
+        assert_eq!(intercept_time(Vec2::new(100.0, 0.0), Vec2::new(0.0, 20.0), 10.0), None);
+    }
+
+    #[test]
+    fn intercept_skips_the_negative_root() {
+        // Slower target: one root lies in the past, the other is the hit
+        let (offset, vel) = (Vec2::new(100.0, 0.0), Vec2::new(0.0, 5.0));
+        let t = intercept_time(offset, vel, 10.0).unwrap();
+        assert!(t > 0.0);
+        assert!(meets(offset, vel, 10.0, t));
+    }
+
+    #[test]
+    fn intercept_with_both_roots_negative_is_none() {
+        // Faster target running away can never be caught
+        assert_eq!(intercept_time(Vec2::new(100.0, 0.0), Vec2::new(20.0, 0.0), 10.0), None);
+    }
+
+    #[test]
+    fn intercept_faster_target_takes_the_earliest_hit() {
+        // Faster target closing in: both roots are ahead, the first meeting wins
+        let (offset, vel) = (Vec2::new(100.0, 0.0), Vec2::new(-20.0, 0.0));
+        let t = intercept_time(offset, vel, 10.0).unwrap();
+        assert!((t - 100.0 / 30.0).abs() < 1e-9);
+        assert!(meets(offset, vel, 10.0, t));
+    }
+
+    #[test]
+    fn intercept_at_equal_speed_is_linear() {
+        // a = 0: only an approaching target can be met
+        let offset = Vec2::new(100.0, 0.0);
+        let t = intercept_time(offset, Vec2::new(-10.0, 0.0), 10.0).unwrap();
+        assert!((t - 5.0).abs() < 1e-9);
+        assert_eq!(intercept_time(offset, Vec2::new(10.0, 0.0), 10.0), None);
+        assert_eq!(intercept_time(offset, Vec2::new(0.0, 10.0), 10.0), None);
+    }
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        (a - b).length() < 1e-9
+    }
+
+    #[test]
+    fn flee_runs_directly_away_at_full_speed() {
+        let step = flee(Vec2::new(10.0, 10.0), Vec2::new(13.0, 14.0), 5.0);
+        assert!(close(step, Vec2::new(-3.0, -4.0)));
+        assert_eq!(flee(Vec2::new(1.0, 1.0), Vec2::new(1.0, 1.0), 5.0), Vec2::ZERO);
+    }
+
+    #[test]
+    fn pursue_leads_by_the_travel_time() {
+        // 100 away at speed 10 = 10 frames; the target moves 50 up in that time
+        let step = pursue(Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::new(0.0, 5.0), 10.0, 60.0);
+        assert!(close(step, Vec2::new(100.0, 50.0).normalized() * 10.0));
+
+        // Capped at max_lookahead frames
+        let step = pursue(Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::new(0.0, 5.0), 10.0, 2.0);
+        assert!(close(step, Vec2::new(100.0, 10.0).normalized() * 10.0));
+    }
+
+    #[test]
+    fn evade_flees_the_predicted_position() {
+        // A threat passing above will be straight overhead in 10 frames
+        let step = evade(Vec2::ZERO, Vec2::new(-50.0, 100.0), Vec2::new(5.0, 0.0), 10.0, 60.0);
+        let threat_at = Vec2::new(-50.0, 100.0) + Vec2::new(5.0, 0.0) * (Vec2::new(-50.0, 100.0).length() / 10.0);
+        assert!(close(step, -threat_at.normalized() * 10.0));
+        assert!(step.y < 0.0);
+    }
+
+    #[test]
+    fn approaching_obstacles_are_evaded() {
+        let pos = Vec2::ZERO;
+        let incoming = [(Vec2::new(50.0, 0.0), Vec2::new(-6.0, 0.0))];
+        let push = avoid_obstacles(pos, incoming, 120.0, 80.0, 2.0, 5.0);
+        assert!(close(push, Vec2::new(-2.0, 0.0)));
+
+        // Moving away, or outside detect_radius
+        let receding = [(Vec2::new(-100.0, 0.0), Vec2::new(-6.0, 0.0)), (Vec2::new(200.0, 0.0), Vec2::new(-6.0, 0.0))];
+        assert_eq!(avoid_obstacles(pos, receding, 120.0, 80.0, 2.0, 5.0), Vec2::ZERO);
+    }
+}
@@ -2,31 +2,20 @@
 use wasm_bindgen::prelude::*;
 use serde::{Serialize, Deserialize};
 
-// Enemy data stride: [x, y, vx, vy, type_id, is_zombie, is_stealth, radius, hp, max_hp,
+use crate::archetype::{self, Archetype, ArchetypeId};
+use crate::flowfield::FlowField;
+use crate::fsm::AiMode;
+use crate::squad::SquadShape;
+use crate::steering::Vec2;
+use faction::{FactionId, Hostility};
+
+// Enemy data stride: [x, y, vx, vy, type_id, faction, is_stealth, radius, hp, max_hp,
 //                     shoot_cooldown, angle, bounce_boost_frames, hit_cooldown,
 //                     charge_cooldown, charge_frames, aggression, pulse_phase,
-//                     zombie_lifetime, stealth_wave_phase, split_level]
-pub const ENEMY_STRIDE: usize = 21;
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum EnemyType {
-    Basic = 0,
-    Elite = 1,
-    Rammer = 2,
-    Exploder = 3,
-}
-
-impl From<f64> for EnemyType {
-    fn from(v: f64) -> Self {
-        match v as i32 {
-            0 => EnemyType::Basic,
-            1 => EnemyType::Elite,
-            2 => EnemyType::Rammer,
-            3 => EnemyType::Exploder,
-            _ => EnemyType::Basic,
-        }
-    }
-}
+//                     zombie_lifetime, stealth_wave_phase, split_level, visibility,
+//                     ai_mode, state_frames, squad, squad_slot, squad_shape,
+//                     speed_scale]
+pub const ENEMY_STRIDE: usize = 28;
 
 #[derive(Debug, Clone)]
 pub struct EnemyState {
@@ -34,8 +23,9 @@ pub struct EnemyState {
     pub y: f64,
     pub vx: f64,
     pub vy: f64,
-    pub enemy_type: EnemyType,
-    pub is_zombie: bool,
+    pub enemy_type: ArchetypeId,
+    // Allegiance (faction crate); zombies are faction::ZOMBIE
+    pub faction: FactionId,
     pub is_stealth: bool,
     pub radius: f64,
     pub hp: f64,
@@ -51,6 +41,15 @@ pub struct EnemyState {
     pub zombie_lifetime: f64,
     pub stealth_wave_phase: f64,
     pub split_level: f64,
+    pub visibility: f64,
+    pub ai_mode: AiMode,
+    pub state_frames: f64,
+    // Squad id (0 = none) and slot in it (0 = leader); see squad.rs
+    pub squad: u32,
+    pub squad_slot: usize,
+    pub squad_shape: SquadShape,
+    // Multiplier on the archetype speed (wave ramp); 0 or less reads as 1
+    pub speed_scale: f64,
 }
 
 impl EnemyState {
@@ -60,8 +59,8 @@ impl EnemyState {
             y: data[1],
             vx: data[2],
             vy: data[3],
-            enemy_type: EnemyType::from(data[4]),
-            is_zombie: data[5] > 0.5,
+            enemy_type: data[4].max(0.0) as ArchetypeId,
+            faction: faction::from_f64(data[5]),
             is_stealth: data[6] > 0.5,
             radius: data[7],
             hp: data[8],
@@ -77,17 +76,61 @@ impl EnemyState {
             zombie_lifetime: data[18],
             stealth_wave_phase: data[19],
             split_level: data[20],
+            visibility: data[21],
+            ai_mode: AiMode::from(data[22]),
+            state_frames: data[23],
+            squad: data[24].max(0.0) as u32,
+            squad_slot: data[25].max(0.0) as usize,
+            squad_shape: SquadShape::from(data[26]),
+            speed_scale: if data[27] > 0.0 { data[27] } else { 1.0 },
         }
     }
 
+    pub fn write_to_slice(&self, data: &mut [f64]) {
+        data[0] = self.x;
+        data[1] = self.y;
+        data[2] = self.vx;
+        data[3] = self.vy;
+        data[4] = self.enemy_type as f64;
+        data[5] = self.faction as f64;
+        data[6] = if self.is_stealth { 1.0 } else { 0.0 };
+        data[7] = self.radius;
+        data[8] = self.hp;
+        data[9] = self.max_hp;
+        data[10] = self.shoot_cooldown;
+        data[11] = self.angle;
+        data[12] = self.bounce_boost_frames;
+        data[13] = self.hit_cooldown;
+        data[14] = self.charge_cooldown;
+        data[15] = self.charge_frames;
+        data[16] = self.aggression;
+        data[17] = self.pulse_phase;
+        data[18] = self.zombie_lifetime;
+        data[19] = self.stealth_wave_phase;
+        data[20] = self.split_level;
+        data[21] = self.visibility;
+        data[22] = self.ai_mode as i32 as f64;
+        data[23] = self.state_frames;
+        data[24] = self.squad as f64;
+        data[25] = self.squad_slot as f64;
+        data[26] = self.squad_shape as i32 as f64;
+        data[27] = self.speed_scale;
+    }
+
+    // Archetype speed scaled for this enemy
+    #[inline]
+    pub fn speed(&self, archetype: &Archetype) -> f64 {
+        archetype.speed * self.speed_scale
+    }
+
     pub fn to_js_value(&self) -> JsValue {
         let arr = js_sys::Array::new();
         arr.push(&JsValue::from_f64(self.x));
         arr.push(&JsValue::from_f64(self.y));
         arr.push(&JsValue::from_f64(self.vx));
         arr.push(&JsValue::from_f64(self.vy));
-        arr.push(&JsValue::from_f64(self.enemy_type as i32 as f64));
-        arr.push(&JsValue::from_bool(self.is_zombie));
+        arr.push(&JsValue::from_f64(self.enemy_type as f64));
+        arr.push(&JsValue::from_f64(self.faction as f64));
         arr.push(&JsValue::from_bool(self.is_stealth));
         arr.push(&JsValue::from_f64(self.radius));
         arr.push(&JsValue::from_f64(self.hp));
@@ -103,10 +146,78 @@ impl EnemyState {
         arr.push(&JsValue::from_f64(self.zombie_lifetime));
         arr.push(&JsValue::from_f64(self.stealth_wave_phase));
         arr.push(&JsValue::from_f64(self.split_level));
+        arr.push(&JsValue::from_f64(self.visibility));
+        arr.push(&JsValue::from_f64(self.ai_mode as i32 as f64));
+        arr.push(&JsValue::from_f64(self.state_frames));
+        arr.push(&JsValue::from_f64(self.squad as f64));
+        arr.push(&JsValue::from_f64(self.squad_slot as f64));
+        arr.push(&JsValue::from_f64(self.squad_shape as i32 as f64));
+        arr.push(&JsValue::from_f64(self.speed_scale));
         arr.into()
     }
 }
 
+// Per-tick world inputs shared by every enemy update
+// module_data stride: [x, y, radius]; projectile_data stride: [x, y, vx, vy, faction]
+pub struct TickContext<'a> {
+    pub ship_x: f64,
+    pub ship_y: f64,
+    pub ship_vx: f64,
+    pub ship_vy: f64,
+    pub ship_radius: f64,
+    pub module_data: &'a [f64],
+    pub projectile_data: &'a [f64],
+    pub canvas_width: f64,
+    pub canvas_height: f64,
+    pub shield_active: bool,
+    pub archetypes: &'a [Archetype],
+    // Snapshot of the faction relations for this tick
+    pub hostility: Hostility,
+    // Paths to the ship around marked obstacles; None when the arena is open
+    pub flow_field: Option<&'a FlowField>,
+}
+
+impl TickContext<'_> {
+    #[inline]
+    pub fn archetype(&self, id: ArchetypeId) -> &Archetype {
+        archetype::lookup(self.archetypes, id)
+    }
+
+    // Whether enemies of `faction` hunt the ship (and its modules)
+    #[inline]
+    pub fn hunts_ship(&self, faction: FactionId) -> bool {
+        self.hostility.is_hostile(faction, faction::PLAYER)
+    }
+
+    // Heading from (x, y) along the flow field towards the ship, if one is in use
+    #[inline]
+    pub fn path_to_ship(&self, x: f64, y: f64) -> Option<Vec2> {
+        self.flow_field.and_then(|field| field.sample(x, y))
+    }
+}
+
+#[cfg(test)]
+impl<'a> TickContext<'a> {
+    // 800x600 arena, still ship in the centre, no modules, projectiles or flow field
+    pub fn for_test(archetypes: &'a [Archetype]) -> Self {
+        TickContext {
+            ship_x: 400.0,
+            ship_y: 300.0,
+            ship_vx: 0.0,
+            ship_vy: 0.0,
+            ship_radius: 20.0,
+            module_data: &[],
+            projectile_data: &[],
+            canvas_width: 800.0,
+            canvas_height: 600.0,
+            shield_active: false,
+            archetypes,
+            hostility: faction::Hostility::default(),
+            flow_field: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModuleTarget {
     pub has_target: bool,
@@ -142,28 +253,142 @@ impl ModuleTarget {
 pub const TWO_PI: f64 = std::f64::consts::PI * 2.0;
 pub const HALF_PI: f64 = std::f64::consts::PI / 2.0;
 
-// Rammer configuration
-pub struct RammerConfig;
+// Rammer configuration - runtime tunable (see config.rs); field names serialize
+// to the same SCREAMING_CASE keys as the JS RAMMER table
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct RammerConfig {
+    pub base_max: f64,
+    pub boost_max: f64,
+    pub rest: f64,
+    pub damp: f64,
+    pub boost_frames: f64,
+    pub hit_cd: f64,
+    pub steer: f64,
+    pub thrust: f64,
+    pub charge_dist: f64,
+    pub charge_prob: f64,
+    pub charge_frames: f64,
+    pub charge_speed_bonus: f64,
+    pub dodge_dist: f64,
+    pub dodge_force: f64,
+    pub orbit_break_radius: f64,
+    pub orbit_tangent_damp: f64,
+    pub close_steer: f64,
+    pub min_fwd: f64,
+    pub shield_padding: f64,
+    pub hit_damage: f64,
+    pub tip_align: f64,
+    pub tip_damage: f64,
+    pub telegraph_frames: f64,
+    pub telegraph_thrust: f64,
+    pub retreat_frames: f64,
+    pub predict_strength: f64,
+    pub predict_max_frames: f64,
+}
+
+impl Default for RammerConfig {
+    fn default() -> Self {
+        Self {
+            base_max: 9.0,
+            boost_max: 28.0,
+            rest: 1.6,
+            damp: 0.985,
+            boost_frames: 14.0,
+            hit_cd: 4.0,
+            steer: 0.5,
+            thrust: 1.2,
+            charge_dist: 300.0,
+            charge_prob: 0.02,
+            charge_frames: 26.0,
+            charge_speed_bonus: 6.0,
+            dodge_dist: 120.0,
+            dodge_force: 2.2,
+            orbit_break_radius: 140.0,
+            orbit_tangent_damp: 0.9,
+            close_steer: 0.75,
+            min_fwd: 0.8,
+            shield_padding: 40.0,
+            hit_damage: 5.0,
+            tip_align: 0.7,
+            tip_damage: 10.0,
+            telegraph_frames: 12.0,
+            telegraph_thrust: 0.25,
+            retreat_frames: 45.0,
+            predict_strength: 1.0,
+            predict_max_frames: 45.0,
+        }
+    }
+}
 
 impl RammerConfig {
-    pub const BASE_MAX: f64 = 9.0;
-    pub const BOOST_MAX: f64 = 28.0;
-    pub const REST: f64 = 1.6;
-    pub const DAMP: f64 = 0.985;
-    pub const BOOST_FRAMES: f64 = 14.0;
-    pub const HIT_CD: f64 = 4.0;
-    pub const STEER: f64 = 0.5;
-    pub const THRUST: f64 = 1.2;
-    pub const CHARGE_DIST: f64 = 300.0;
-    pub const CHARGE_PROB: f64 = 0.02;
-    pub const CHARGE_FRAMES: f64 = 26.0;
-    pub const CHARGE_SPEED_BONUS: f64 = 6.0;
-    pub const DODGE_DIST: f64 = 120.0;
-    pub const DODGE_FORCE: f64 = 2.2;
-    pub const ORBIT_BREAK_RADIUS: f64 = 140.0;
-    pub const ORBIT_TANGENT_DAMP: f64 = 0.9;
-    pub const CLOSE_STEER: f64 = 0.75;
-    pub const MIN_FWD: f64 = 0.8;
+    pub fn validate(&self) -> Result<(), String> {
+        fn check(name: &str, value: f64, min: f64, max: f64) -> Result<(), String> {
+            if value.is_finite() && value >= min && value <= max {
+                Ok(())
+            } else {
+                Err(format!("RammerConfig.{} = {} is outside [{}, {}]", name, value, min, max))
+            }
+        }
+
+        check("BASE_MAX", self.base_max, 0.1, 100.0)?;
+        check("BOOST_MAX", self.boost_max, self.base_max, 200.0)?;
+        check("REST", self.rest, 0.0, 3.0)?;
+        check("DAMP", self.damp, 0.5, 1.0)?;
+        check("BOOST_FRAMES", self.boost_frames, 0.0, 600.0)?;
+        check("HIT_CD", self.hit_cd, 0.0, 600.0)?;
+        check("STEER", self.steer, 0.0, 1.0)?;
+        check("THRUST", self.thrust, 0.0, 20.0)?;
+        check("CHARGE_DIST", self.charge_dist, 0.0, 5000.0)?;
+        check("CHARGE_PROB", self.charge_prob, 0.0, 1.0)?;
+        check("CHARGE_FRAMES", self.charge_frames, 0.0, 600.0)?;
+        check("CHARGE_SPEED_BONUS", self.charge_speed_bonus, 0.0, 100.0)?;
+        check("DODGE_DIST", self.dodge_dist, 0.0, 5000.0)?;
+        check("DODGE_FORCE", self.dodge_force, 0.0, 50.0)?;
+        check("ORBIT_BREAK_RADIUS", self.orbit_break_radius, 0.0, 5000.0)?;
+        check("ORBIT_TANGENT_DAMP", self.orbit_tangent_damp, 0.0, 1.0)?;
+        check("CLOSE_STEER", self.close_steer, 0.0, 1.0)?;
+        check("MIN_FWD", self.min_fwd, 0.0, 100.0)?;
+        check("SHIELD_PADDING", self.shield_padding, 0.0, 500.0)?;
+        check("HIT_DAMAGE", self.hit_damage, 0.0, 1000.0)?;
+        check("TIP_ALIGN", self.tip_align, -1.0, 1.0)?;
+        check("TIP_DAMAGE", self.tip_damage, 0.0, 1000.0)?;
+        check("TELEGRAPH_FRAMES", self.telegraph_frames, 0.0, 600.0)?;
+        check("TELEGRAPH_THRUST", self.telegraph_thrust, 0.0, 1.0)?;
+        check("RETREAT_FRAMES", self.retreat_frames, 0.0, 600.0)?;
+        check("PREDICT_STRENGTH", self.predict_strength, 0.0, 2.0)?;
+        check("PREDICT_MAX_FRAMES", self.predict_max_frames, 0.0, 600.0)?;
+        Ok(())
+    }
+}
+
+// Exploder detonation (mirrors the exploder branch of Enemy.update in gameAI.js)
+pub struct ExploderConfig;
+
+impl ExploderConfig {
+    pub const BLAST_RADIUS_SCALE: f64 = 1.5;
+    pub const SHIP_DAMAGE: f64 = 20.0;
+    pub const ENEMY_DAMAGE: f64 = 50.0;
+}
+
+// Stealth cloak tuning
+pub struct StealthConfig;
+
+impl StealthConfig {
+    pub const CLOAKED_VISIBILITY: f64 = 0.2;
+    pub const SHIMMER: f64 = 0.08;
+    pub const DECLOAK_RATE: f64 = 0.25;
+    pub const RECLOAK_RATE: f64 = 0.04;
+    // Modules can lock onto a cloaked enemy only inside this radius
+    pub const DETECTION_RADIUS: f64 = 150.0;
+    pub const REVEALED_VISIBILITY: f64 = 0.5;
+}
+
+// Whether an observer at squared distance `dist_sq` can see an enemy with the given visibility
+#[inline]
+pub fn is_detectable(visibility: f64, dist_sq: f64) -> bool {
+    visibility >= StealthConfig::REVEALED_VISIBILITY
+        || dist_sq <= StealthConfig::DETECTION_RADIUS * StealthConfig::DETECTION_RADIUS
 }
 
 #[inline]
@@ -181,3 +406,33 @@ pub fn clamp(value: f64, min: f64, max: f64) -> f64 {
         value
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloaked_enemies_are_detectable_only_up_close() {
+        let radius = StealthConfig::DETECTION_RADIUS;
+        let cloaked = StealthConfig::CLOAKED_VISIBILITY;
+        assert!(is_detectable(cloaked, radius * radius));
+        assert!(!is_detectable(cloaked, (radius + 1.0) * (radius + 1.0)));
+        assert!(is_detectable(StealthConfig::REVEALED_VISIBILITY, 1e9));
+    }
+
+    #[test]
+    fn unset_speed_scale_reads_as_one() {
+        archetype::with_archetypes(|archetypes| {
+            let mut record = [0.0; ENEMY_STRIDE];
+            let enemy = EnemyState::from_slice(&record);
+            assert_eq!(enemy.speed(&archetypes[0]), archetypes[0].speed);
+
+            record[27] = 1.05;
+            let enemy = EnemyState::from_slice(&record);
+            assert_eq!(enemy.speed(&archetypes[0]), archetypes[0].speed * 1.05);
+            let mut written = [0.0; ENEMY_STRIDE];
+            enemy.write_to_slice(&mut written);
+            assert_eq!(written[27], 1.05);
+        });
+    }
+}
@@ -1,13 +1,34 @@
 // AI WASM Wrapper - JavaScript interface for AI WASM module
 let aiWasmModule = null;
 let aiWasmReady = false;
+let aiWasmMemory = null;
+let aiState = null;
+let moduleController = null;
+let moduleFormation = null;
+
+// Floats per enemy in the AI enemy buffer (ENEMY_STRIDE in ai-wasm/src/types.rs)
+const AI_ENEMY_STRIDE = 28;
+// Floats per shot from ModuleController.update (MODULE_SHOT_STRIDE in ai-wasm/src/module_ai.rs)
+const MODULE_SHOT_STRIDE = 7;
+// Floats per module from ModuleFormation.update (FORMATION_STRIDE in ai-wasm/src/formation.rs)
+const FORMATION_STRIDE = 4;
+// Floats per module in solve_module_fire output (FIRE_SOLUTION_STRIDE in ai-wasm/src/module_ai.rs)
+const FIRE_SOLUTION_STRIDE = 3;
 
 // Initialize AI WASM module
 async function initAIWasm() {
     try {
         const module = await import('./ai-wasm/pkg/ai_wasm.js');
-        await module.default(); // Initialize WASM
+        const wasm = await module.default(); // Initialize WASM
         aiWasmModule = module;
+        aiWasmMemory = wasm.memory;
+
+        // Persistent state for the shared-memory update path
+        aiState = new module.AiState();
+        moduleController = new module.ModuleController();
+        moduleFormation = new module.ModuleFormation();
+        addFactionMatrixSink(values => module.set_faction_matrix(values));
+        refreshArchetypes();
         aiWasmReady = true;
         console.log('✅ AI WASM module loaded successfully');
         return true;
@@ -19,9 +40,41 @@ async function initAIWasm() {
     }
 }
 
+// Enemy archetypes (ai-wasm/src/archetypes.json); the enemy type_id is the index in this list
+let archetypes = [];
+let archetypeIds = new Map();
+
+function refreshArchetypes() {
+    archetypes = aiWasmModule.get_archetypes();
+    archetypeIds = new Map(archetypes.map((a, id) => [a.name, id]));
+}
+
+function archetypeId(type) {
+    return archetypeIds.get(type) ?? 0;
+}
+
+// Archetype definition by name (radius, hp, speed, score, drops, ...), or null
+function getArchetype(type) {
+    const id = archetypeIds.get(type);
+    return id === undefined ? null : archetypes[id];
+}
+
+// Replace the archetype list from a JSON string; existing type names keep working if still present
+function loadArchetypesWasm(json) {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    try {
+        aiWasmModule.load_archetypes(json);
+    } catch (error) {
+        console.error('Invalid archetype JSON:', error);
+        return false;
+    }
+    refreshArchetypes();
+    return true;
+}
+
 // Prepare enemy data for WASM (flat array format)
 function prepareEnemyData(enemies) {
-    const STRIDE = 21;
+    const STRIDE = AI_ENEMY_STRIDE;
     const data = new Float64Array(enemies.length * STRIDE);
 
     for (let i = 0; i < enemies.length; i++) {
@@ -33,15 +86,9 @@ function prepareEnemyData(enemies) {
         data[offset + 2] = e.vx || 0;
         data[offset + 3] = e.vy || 0;
 
-        // Type mapping: basic=0, elite=1, rammer=2, exploder=3
-        let typeId = 0;
-        if (e.type === 'basic') typeId = 0;
-        else if (e.type === 'elite') typeId = 1;
-        else if (e.type === 'rammer') typeId = 2;
-        else if (e.type === 'exploder') typeId = 3;
-        data[offset + 4] = typeId;
+        data[offset + 4] = archetypeId(e.type);
 
-        data[offset + 5] = e.isZombie ? 1.0 : 0.0;
+        data[offset + 5] = e.faction;
         data[offset + 6] = e.isStealth ? 1.0 : 0.0;
         data[offset + 7] = e.radius;
         data[offset + 8] = e.hp;
@@ -57,6 +104,13 @@ function prepareEnemyData(enemies) {
         data[offset + 18] = e.zombieLifetime || 0;
         data[offset + 19] = e.stealthWavePhase || 0;
         data[offset + 20] = e.splitLevel || 0;
+        data[offset + 21] = e.visibility !== undefined ? e.visibility : (e.isStealth ? 0.2 : 1.0);
+        data[offset + 22] = e.aiMode || 0;
+        data[offset + 23] = e.stateFrames || 0;
+        data[offset + 24] = e.squad || 0;
+        data[offset + 25] = e.squadSlot || 0;
+        data[offset + 26] = e.squadShape || 0;
+        data[offset + 27] = e.speedScale || 1.0;
     }
 
     return data;
@@ -82,68 +136,225 @@ function prepareProjectileData(projectiles) {
         data[i * 5 + 1] = p.y;
         data[i * 5 + 2] = p.vx;
         data[i * 5 + 3] = p.vy;
-
-        // Type mapping: player=0, module=1, zombie=2, enemy=3
-        let typeNum = 3;
-        if (p.type === 'player') typeNum = 0;
-        else if (p.type === 'module') typeNum = 1;
-        else if (p.type === 'zombie') typeNum = 2;
-        data[i * 5 + 4] = typeNum;
+        data[i * 5 + 4] = p.faction;
     }
     return data;
 }
 
-// Update enemies using WASM
-function updateEnemiesWasm(enemies, ship, modules, projectiles, canvas) {
-    if (!aiWasmReady || !aiWasmModule) {
+// AI event records (see ai-wasm/src/events.rs): [kind, index, a, b, c, d, e, f]
+const AI_EVENT_STRIDE = 8;
+const AI_EVENT = {
+    SHOT: 0,
+    SELF_DAMAGE: 1,
+    SHIP_DAMAGE: 2,
+    ZOMBIE_EXPIRED: 3,
+    CHARGE_STARTED: 4,
+    KILLED: 5,
+    EXPLODED: 6,
+    AREA_DAMAGE: 7,
+    STATE_CHANGED: 8,
+    SQUAD_LEADER: 9
+};
+// Enemy decision modes (AiMode in ai-wasm/src/fsm.rs)
+const AI_MODE = {
+    CRUISE: 0,
+    TELEGRAPH: 1,
+    CHARGE: 2,
+    REBOUND: 3,
+    ORBIT_BREAK: 4,
+    RETREAT: 5
+};
+const AI_MODE_NAMES = ['cruise', 'telegraph', 'charge', 'rebound', 'orbitBreak', 'retreat'];
+const DAMAGE_CAUSE_LOG = { 3: 'rammerCollision', 4: 'exploderExplosion' };
+
+// Apply every side effect reported by the AI tick in one pass
+// effects: { particles, getParticle, supernovaEffects, ExploderExplosion } (optional)
+function applyAIEvents(events, projectiles, gameState, effects) {
+    const Projectile = window.Projectile;
+
+    for (let offset = 0; offset < events.length; offset += AI_EVENT_STRIDE) {
+        const kind = events[offset];
+
+        switch (kind) {
+            case AI_EVENT.SHOT: {
+                // Shots carry the shooter's faction; zombies keep their own bullet look
+                const faction = events[offset + 7];
+                const projectile = new Projectile(
+                    events[offset + 2], events[offset + 3],
+                    events[offset + 4], events[offset + 5],
+                    faction === FACTION.ZOMBIE ? 'zombie' : 'enemy', events[offset + 6]
+                );
+                projectile.faction = faction;
+                projectiles.push(projectile);
+                break;
+            }
+            case AI_EVENT.SHIP_DAMAGE:
+                if (gameState) {
+                    const amount = events[offset + 2];
+                    gameState.energy = Math.max(0, gameState.energy - amount);
+                    if (window.devMode) window.devMode.logDamage(DAMAGE_CAUSE_LOG[events[offset + 3]], amount);
+                }
+                break;
+            case AI_EVENT.EXPLODED:
+                if (effects) {
+                    const x = events[offset + 2], y = events[offset + 3];
+                    const isZombie = events[offset + 5] === FACTION.ZOMBIE;
+                    for (let i = 0; i < 30; i++) effects.particles.push(effects.getParticle(x, y, isZombie));
+                    effects.supernovaEffects.push(new effects.ExploderExplosion(x, y));
+                }
+                break;
+            case AI_EVENT.SELF_DAMAGE:
+            case AI_EVENT.AREA_DAMAGE:
+            case AI_EVENT.ZOMBIE_EXPIRED:
+            case AI_EVENT.KILLED:
+                // HP is already written back; the main loop's hp <= 0 sweep runs handleEnemyDeath
+                break;
+            case AI_EVENT.CHARGE_STARTED:
+            case AI_EVENT.STATE_CHANGED:
+                // aiMode is already written back; Enemy.draw shows the telegraph
+                break;
+            case AI_EVENT.SQUAD_LEADER:
+                // squad / squadSlot are already written back; Enemy.draw marks the leader
+                break;
+        }
+    }
+}
+
+// Write enemies straight into the AiState buffer (same layout as prepareEnemyData)
+function writeEnemyBuffer(enemies) {
+    const STRIDE = AI_ENEMY_STRIDE;
+    const size = enemies.length * STRIDE;
+    aiState.ensure_enemy_buffer_size(size);
+    const data = new Float64Array(aiWasmMemory.buffer, aiState.get_enemy_buffer_ptr(), size);
+
+    for (let i = 0; i < enemies.length; i++) {
+        const e = enemies[i];
+        const offset = i * STRIDE;
+
+        data[offset] = e.x;
+        data[offset + 1] = e.y;
+        data[offset + 2] = e.vx || 0;
+        data[offset + 3] = e.vy || 0;
+
+        data[offset + 4] = archetypeId(e.type);
+
+        data[offset + 5] = e.faction;
+        data[offset + 6] = e.isStealth ? 1.0 : 0.0;
+        data[offset + 7] = e.radius;
+        data[offset + 8] = e.hp;
+        data[offset + 9] = e.maxHp;
+        data[offset + 10] = e.shootCooldown || 0;
+        data[offset + 11] = e.angle || 0;
+        data[offset + 12] = e.bounceBoostFrames || 0;
+        data[offset + 13] = e.hitCooldown || 0;
+        data[offset + 14] = e.chargeCooldown || 0;
+        data[offset + 15] = e.chargeFrames || 0;
+        data[offset + 16] = e.aggression || 1.0;
+        data[offset + 17] = e.pulsePhase || 0;
+        data[offset + 18] = e.zombieLifetime || 0;
+        data[offset + 19] = e.stealthWavePhase || 0;
+        data[offset + 20] = e.splitLevel || 0;
+        data[offset + 21] = e.visibility !== undefined ? e.visibility : (e.isStealth ? 0.2 : 1.0);
+        data[offset + 22] = e.aiMode || 0;
+        data[offset + 23] = e.stateFrames || 0;
+        data[offset + 24] = e.squad || 0;
+        data[offset + 25] = e.squadSlot || 0;
+        data[offset + 26] = e.squadShape || 0;
+        data[offset + 27] = e.speedScale || 1.0;
+    }
+}
+
+function writeModuleBuffer(modules) {
+    const size = modules.length * 3;
+    aiState.ensure_mod_buffer_size(size);
+    const data = new Float64Array(aiWasmMemory.buffer, aiState.get_mod_buffer_ptr(), size);
+    for (let i = 0; i < modules.length; i++) {
+        data[i * 3] = modules[i].x;
+        data[i * 3 + 1] = modules[i].y;
+        data[i * 3 + 2] = modules[i].radius;
+    }
+}
+
+function writeProjectileBuffer(projectiles) {
+    const size = projectiles.length * 5;
+    aiState.ensure_proj_buffer_size(size);
+    const data = new Float64Array(aiWasmMemory.buffer, aiState.get_proj_buffer_ptr(), size);
+    for (let i = 0; i < projectiles.length; i++) {
+        const p = projectiles[i];
+        data[i * 5] = p.x;
+        data[i * 5 + 1] = p.y;
+        data[i * 5 + 2] = p.vx;
+        data[i * 5 + 3] = p.vy;
+        data[i * 5 + 4] = p.faction;
+    }
+}
+
+// Update enemies using WASM (shared memory, updated in place)
+function updateEnemiesWasm(enemies, ship, modules, projectiles, canvas, gameState, effects) {
+    if (!aiWasmReady || !aiWasmModule || !aiState) {
         return null; // Fallback to JS
     }
 
     try {
-        const enemyData = prepareEnemyData(enemies);
-        const moduleData = prepareModuleData(modules);
-        const projectileData = prepareProjectileData(projectiles);
+        // Views must be created after ensure_* calls since growing memory detaches old buffers
+        writeEnemyBuffer(enemies);
+        writeModuleBuffer(modules);
+        writeProjectileBuffer(projectiles);
 
-        const results = aiWasmModule.update_enemies(
-            enemyData,
+        aiState.update(
+            enemies.length,
+            modules.length,
+            projectiles.length,
             ship.x,
             ship.y,
+            ship.vx || 0,
+            ship.vy || 0,
             ship.radius,
-            moduleData,
-            projectileData,
             canvas.width,
             canvas.height,
-            false // shield_active - would need to pass gameState
+            !!(gameState && gameState.shieldActive)
         );
 
+        const STRIDE = AI_ENEMY_STRIDE;
+        const updated = new Float64Array(aiWasmMemory.buffer, aiState.get_enemy_buffer_ptr(), enemies.length * STRIDE);
+
         // Apply results back to enemy objects
-        for (let i = 0; i < enemies.length && i < results.length; i++) {
-            const updated = results[i];
+        for (let i = 0; i < enemies.length; i++) {
+            const offset = i * STRIDE;
             const enemy = enemies[i];
 
-            enemy.x = updated[0];
-            enemy.y = updated[1];
-            enemy.vx = updated[2];
-            enemy.vy = updated[3];
+            enemy.x = updated[offset];
+            enemy.y = updated[offset + 1];
+            enemy.vx = updated[offset + 2];
+            enemy.vy = updated[offset + 3];
             // Type remains same
-            enemy.isZombie = updated[5];
-            enemy.isStealth = updated[6];
+            enemy.faction = updated[offset + 5];
+            enemy.isStealth = updated[offset + 6] > 0.5;
             // Radius remains same
-            enemy.hp = updated[8];
+            enemy.hp = updated[offset + 8];
             // maxHp remains same
-            enemy.shootCooldown = updated[10];
-            enemy.angle = updated[11];
-            enemy.bounceBoostFrames = updated[12];
-            enemy.hitCooldown = updated[13];
-            enemy.chargeCooldown = updated[14];
-            enemy.chargeFrames = updated[15];
-            enemy.aggression = updated[16];
-            enemy.pulsePhase = updated[17];
-            enemy.zombieLifetime = updated[18];
-            enemy.stealthWavePhase = updated[19];
-            enemy.splitLevel = updated[20];
+            enemy.shootCooldown = updated[offset + 10];
+            enemy.angle = updated[offset + 11];
+            enemy.bounceBoostFrames = updated[offset + 12];
+            enemy.hitCooldown = updated[offset + 13];
+            enemy.chargeCooldown = updated[offset + 14];
+            enemy.chargeFrames = updated[offset + 15];
+            enemy.aggression = updated[offset + 16];
+            enemy.pulsePhase = updated[offset + 17];
+            enemy.zombieLifetime = updated[offset + 18];
+            enemy.stealthWavePhase = updated[offset + 19];
+            enemy.splitLevel = updated[offset + 20];
+            enemy.visibility = updated[offset + 21];
+            enemy.aiMode = updated[offset + 22];
+            enemy.stateFrames = updated[offset + 23];
+            // Slots are renumbered and engaged members leave; the shape never changes
+            enemy.squad = updated[offset + 24];
+            enemy.squadSlot = updated[offset + 25];
         }
 
+        const events = new Float64Array(aiWasmMemory.buffer, aiState.get_event_buffer_ptr(), aiState.get_event_buffer_len());
+        applyAIEvents(events, projectiles, gameState, effects);
+
         return true;
     } catch (error) {
         console.error('WASM AI update error:', error);
@@ -173,8 +384,21 @@ function queryEnemyNeighborsWasm(x, y) {
     return aiWasmModule.query_enemy_neighbors(x, y);
 }
 
-// Find module targets using WASM
-function findModuleTargetsWasm(modules, enemies) {
+// Module target-assignment strategies (TargetStrategy in ai-wasm/src/module_ai.rs)
+const TARGET_STRATEGY = {
+    NEAREST: 0,
+    THREAT: 1,
+    LOWEST_HP: 2,
+    SPREAD: 3
+};
+
+// Damage of one module shot as configured on the ModuleController; sizes focus fire
+function moduleBulletDamage() {
+    return moduleController.get_config().BULLET_DAMAGE;
+}
+
+// Find module targets using WASM; targets are assigned jointly across `modules`
+function findModuleTargetsWasm(modules, enemies, strategy = TARGET_STRATEGY.NEAREST, ship = null, bulletDamage = null) {
     if (!aiWasmReady || !aiWasmModule) {
         return null; // Fallback to JS
     }
@@ -183,7 +407,10 @@ function findModuleTargetsWasm(modules, enemies) {
         const moduleData = prepareModuleData(modules);
         const enemyData = prepareEnemyData(enemies);
 
-        const results = aiWasmModule.find_module_targets(moduleData, enemyData);
+        const results = aiWasmModule.find_module_targets(
+            moduleData, enemyData, strategy, bulletDamage ?? moduleBulletDamage(),
+            ship ? ship.x : 0, ship ? ship.y : 0
+        );
         return results;
     } catch (error) {
         console.error('WASM module targeting error:', error);
@@ -191,10 +418,389 @@ function findModuleTargetsWasm(modules, enemies) {
     }
 }
 
+// Batched module fire control: exact intercept angles for each module's assigned target
+// Returns [{ targetIndex, angle, timeToHit }] (targetIndex -1 = nothing to shoot), or null
+function solveModuleFireWasm(modules, enemies, bulletSpeed, strategy = TARGET_STRATEGY.NEAREST, ship = null, bulletDamage = null) {
+    if (!aiWasmReady || !aiWasmModule) return null;
+
+    try {
+        const data = aiWasmModule.solve_module_fire(
+            prepareModuleData(modules), prepareEnemyData(enemies), bulletSpeed,
+            bulletDamage ?? moduleBulletDamage(), strategy, ship ? ship.x : 0, ship ? ship.y : 0
+        );
+        const solutions = [];
+        for (let offset = 0; offset < data.length; offset += FIRE_SOLUTION_STRIDE) {
+            solutions.push({ targetIndex: data[offset], angle: data[offset + 1], timeToHit: data[offset + 2] });
+        }
+        return solutions;
+    } catch (error) {
+        console.error('WASM fire control error:', error);
+        return null;
+    }
+}
+
+// Module combat for this frame: cooldowns, target assignment and aiming live in ai-wasm.
+// firing = modules are autonomous (split). Returns [{ moduleIndex, x, y, vx, vy, damage, targetIndex }]
+function updateModuleControllerWasm(modules, enemies, ship, firing) {
+    if (!aiWasmReady || !moduleController) return null;
+
+    try {
+        const count = moduleController.update(prepareModuleData(modules), prepareEnemyData(enemies), ship.x, ship.y, firing);
+        const data = new Float64Array(aiWasmMemory.buffer, moduleController.get_shot_buffer_ptr(), count * MODULE_SHOT_STRIDE);
+        const shots = [];
+        for (let offset = 0; offset < data.length; offset += MODULE_SHOT_STRIDE) {
+            shots.push({
+                moduleIndex: data[offset],
+                x: data[offset + 1],
+                y: data[offset + 2],
+                vx: data[offset + 3],
+                vy: data[offset + 4],
+                damage: data[offset + 5],
+                targetIndex: data[offset + 6]
+            });
+        }
+        return shots;
+    } catch (error) {
+        console.error('WASM module controller error:', error);
+        return null;
+    }
+}
+
+// Deployed module layouts (Formation in ai-wasm/src/formation.rs)
+const MODULE_FORMATION = {
+    RING: 0,
+    LINE: 1,
+    WEDGE: 2,
+    ESCORT: 3
+};
+
+// Move modules for this frame. deployed = ship.isSplit; docked modules orbit the ship,
+// deployed ones hold the selected formation. Writes targetX/targetY and x/y on each module.
+function updateModuleFormationWasm(modules, enemies, ship, deployed) {
+    if (!aiWasmReady || !moduleFormation) return false;
+
+    try {
+        const count = moduleFormation.update(prepareModuleData(modules), prepareEnemyData(enemies),
+            ship.x, ship.y, ship.vx || 0, ship.vy || 0, deployed);
+        const data = new Float64Array(aiWasmMemory.buffer, moduleFormation.get_buffer_ptr(), count * FORMATION_STRIDE);
+        for (let i = 0; i < count; i++) {
+            const offset = i * FORMATION_STRIDE;
+            const m = modules[i];
+            m.targetX = data[offset];
+            m.targetY = data[offset + 1];
+            m.x = data[offset + 2];
+            m.y = data[offset + 3];
+        }
+        return true;
+    } catch (error) {
+        console.error('WASM module formation error:', error);
+        return false;
+    }
+}
+
+// formation: MODULE_FORMATION id or key ('RING', 'LINE', 'WEDGE', 'ESCORT')
+function setModuleFormationWasm(formation) {
+    if (!aiWasmReady || !moduleFormation) return false;
+    const id = typeof formation === 'string' ? MODULE_FORMATION[formation] : formation;
+    if (id === undefined) return false;
+    moduleFormation.set_formation(id);
+    return true;
+}
+
+// Formation tuning: { LERP, SPLIT_LERP, RECALL_LERP, RECALL_FRAMES, MAX_SPEED, RING_RADIUS, ... }
+function getModuleFormationConfigWasm() {
+    if (!aiWasmReady || !moduleFormation) return null;
+    return moduleFormation.get_config();
+}
+
+function setModuleFormationConfigWasm(partial) {
+    if (!aiWasmReady || !moduleFormation) return false;
+    try {
+        moduleFormation.set_config(partial);
+    } catch (error) {
+        console.error('Invalid module formation config:', error);
+        return false;
+    }
+    return true;
+}
+
+// Module weapon tuning: { COOLDOWN, MIN_RANGE, MAX_RANGE, FIRING_ARC, BULLET_SPEED, BULLET_DAMAGE, TARGET_STRATEGY }
+function getModuleWeaponConfigWasm() {
+    if (!aiWasmReady || !moduleController) return null;
+    return moduleController.get_config();
+}
+
+function setModuleWeaponConfigWasm(partial) {
+    if (!aiWasmReady || !moduleController) return false;
+    try {
+        moduleController.set_config(partial);
+    } catch (error) {
+        console.error('Invalid module weapon config:', error);
+        return false;
+    }
+    return true;
+}
+
+// Split a dying enemy into children (archetype "split" rules); returns new Enemy objects
+
+function splitEnemyWasm(enemy) {
+    if (!aiWasmReady || !aiWasmModule) return [];
+
+    const childData = aiWasmModule.split_enemy(prepareEnemyData([enemy]));
+    const Enemy = window.Enemy;
+    const children = [];
+
+    for (let offset = 0; offset < childData.length; offset += AI_ENEMY_STRIDE) {
+        const type = archetypes[childData[offset + 4]]?.name || enemy.type;
+        const child = new Enemy(childData[offset], childData[offset + 1], type, false, enemy.isStealth, childData[offset + 20]);
+        child.faction = childData[offset + 5];
+        child.vx = childData[offset + 2];
+        child.vy = childData[offset + 3];
+        child.radius = childData[offset + 7];
+        child.hp = childData[offset + 8];
+        child.maxHp = childData[offset + 9];
+        child.angle = childData[offset + 11];
+        child.aggression = childData[offset + 16];
+        child.speedScale = childData[offset + 27];
+        child.visibility = childData[offset + 21];
+        children.push(child);
+    }
+
+    return children;
+}
+
+function setSplitRuleWasm(type, children, maxDepth) {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    const id = archetypeIds.get(type);
+    if (id === undefined) return false;
+    try {
+        aiWasmModule.set_split_rule(id, children, maxDepth);
+    } catch (error) {
+        console.error('Invalid split rule:', error);
+        return false;
+    }
+    refreshArchetypes();
+    return true;
+}
+
+// Toggle boids flocking for an archetype by name
+function setArchetypeFlockingWasm(type, enabled) {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    const id = archetypeIds.get(type);
+    if (id === undefined) return false;
+    const ok = aiWasmModule.set_archetype_flocking(id, !!enabled);
+    refreshArchetypes();
+    return ok;
+}
+
+// Flock weights and radii: { NEIGHBOR_RADIUS, SEPARATION_RADIUS, ALIGNMENT, COHESION, SEPARATION, MAX_TURN, TURN_SCALE }
+function getFlockConfigWasm() {
+    if (!aiWasmReady || !aiWasmModule) return null;
+    return aiWasmModule.get_flock_config();
+}
+
+function setFlockConfigWasm(partial) {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    try {
+        aiWasmModule.set_flock_config(partial);
+    } catch (error) {
+        console.error('Invalid flock config:', error);
+        return false;
+    }
+    return true;
+}
+
+function resetFlockConfigWasm() {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    aiWasmModule.reset_flock_config();
+    return true;
+}
+
+// [dx, dy] per slot (slot 0 = leader) for a squad facing `heading`; null when wasm is not ready
+function getSquadSlotOffsetsWasm(shape, members, heading) {
+    if (!aiWasmReady || !aiWasmModule) return null;
+    return aiWasmModule.get_squad_slot_offsets(shape, members, heading);
+}
+
+// Squad spacing and engagement: { SPACING, ENGAGE_RADIUS, CATCH_UP, SLOW_RADIUS }
+function getSquadConfigWasm() {
+    if (!aiWasmReady || !aiWasmModule) return null;
+    return aiWasmModule.get_squad_config();
+}
+
+function setSquadConfigWasm(partial) {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    try {
+        aiWasmModule.set_squad_config(partial);
+    } catch (error) {
+        console.error('Invalid squad config:', error);
+        return false;
+    }
+    return true;
+}
+
+function resetSquadConfigWasm() {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    aiWasmModule.reset_squad_config();
+    return true;
+}
+
+// Flow field: ship-hunting chasers and exploders path around blocked rectangles and avoid
+// costly ones (cost >= 1). Regions stack; later ones win where they overlap.
+function addFlowObstacleWasm(x, y, width, height) {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    try {
+        aiWasmModule.add_flow_obstacle(x, y, width, height);
+    } catch (error) {
+        console.error('Invalid flow obstacle:', error);
+        return false;
+    }
+    return true;
+}
+
+function addFlowCostWasm(x, y, width, height, cost) {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    try {
+        aiWasmModule.add_flow_cost(x, y, width, height, cost);
+    } catch (error) {
+        console.error('Invalid flow cost:', error);
+        return false;
+    }
+    return true;
+}
+
+function clearFlowFieldWasm() {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    aiWasmModule.clear_flow_field();
+    return true;
+}
+
+function setFlowCellSizeWasm(size) {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    try {
+        aiWasmModule.set_flow_cell_size(size);
+    } catch (error) {
+        console.error('Invalid flow cell size:', error);
+        return false;
+    }
+    return true;
+}
+
+// { x, y } unit heading towards the ship, or null in open arenas / unreachable spots
+function getFlowDirectionWasm(x, y) {
+    if (!aiWasmReady || !aiWasmModule) return null;
+    const dir = aiWasmModule.get_flow_direction(x, y);
+    return dir.length === 2 ? { x: dir[0], y: dir[1] } : null;
+}
+
+// Difficulty ramp: global aggression multiplier for the wave
+function setWaveAggressionWasm(wave) {
+    if (!aiWasmReady || !aiWasmModule) return 1.0;
+    return aiWasmModule.set_wave_aggression(wave);
+}
+
+// Global aggression multiplier, clamped to [0.25, 3]; false for a non-finite value
+function setGlobalAggressionWasm(value) {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    try {
+        aiWasmModule.set_global_aggression(value);
+    } catch (error) {
+        console.error('Invalid global aggression:', error);
+        return false;
+    }
+    return true;
+}
+
+// Per-enemy aggression travels in the enemy record (offset 16); false for a non-finite value
+function setEnemyAggression(enemy, value) {
+    if (!Number.isFinite(value)) return false;
+    enemy.aggression = Math.min(3.0, Math.max(0.25, value));
+    return true;
+}
+
+// Live rammer tuning (dev panel); keeps the JS RAMMER table in sync for the fallback path
+function getRammerConfigWasm() {
+    if (!aiWasmReady || !aiWasmModule) return null;
+    return aiWasmModule.get_rammer_config();
+}
+
+function setRammerConfigWasm(partial) {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    try {
+        aiWasmModule.set_rammer_config(partial);
+    } catch (error) {
+        console.error('Invalid rammer config:', error);
+        return false;
+    }
+    if (window.RAMMER) Object.assign(window.RAMMER, partial);
+    return true;
+}
+
+function resetRammerConfigWasm() {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    aiWasmModule.reset_rammer_config();
+    const defaults = aiWasmModule.get_rammer_config();
+    if (window.RAMMER) {
+        for (const key of Object.keys(window.RAMMER)) window.RAMMER[key] = defaults[key];
+    }
+    return true;
+}
+
+// Seed the AI RNG so runs can be reproduced
+function setAISeedWasm(seed) {
+    if (!aiWasmReady || !aiWasmModule) return false;
+    aiWasmModule.set_seed(seed >>> 0);
+    return true;
+}
+
+function getAIRngStateWasm() {
+    if (!aiWasmReady || !aiWasmModule) return null;
+    return aiWasmModule.get_rng_state();
+}
+
 // Export functions
 window.initAIWasm = initAIWasm;
 window.updateEnemiesWasm = updateEnemiesWasm;
 window.rebuildEnemyGridAI = rebuildEnemyGridWasm;
 window.queryEnemyNeighborsAI = queryEnemyNeighborsWasm;
 window.findModuleTargetsWasm = findModuleTargetsWasm;
+window.solveModuleFireWasm = solveModuleFireWasm;
+window.TARGET_STRATEGY = TARGET_STRATEGY;
+window.updateModuleControllerWasm = updateModuleControllerWasm;
+window.getModuleWeaponConfigWasm = getModuleWeaponConfigWasm;
+window.setModuleWeaponConfigWasm = setModuleWeaponConfigWasm;
+window.MODULE_FORMATION = MODULE_FORMATION;
+window.updateModuleFormationWasm = updateModuleFormationWasm;
+window.setModuleFormationWasm = setModuleFormationWasm;
+window.getModuleFormationConfigWasm = getModuleFormationConfigWasm;
+window.setModuleFormationConfigWasm = setModuleFormationConfigWasm;
+window.applyAIEvents = applyAIEvents;
+window.splitEnemyWasm = splitEnemyWasm;
+window.setSplitRuleWasm = setSplitRuleWasm;
+window.setArchetypeFlockingWasm = setArchetypeFlockingWasm;
+window.getFlockConfigWasm = getFlockConfigWasm;
+window.setFlockConfigWasm = setFlockConfigWasm;
+window.resetFlockConfigWasm = resetFlockConfigWasm;
+window.getSquadSlotOffsetsWasm = getSquadSlotOffsetsWasm;
+window.getSquadConfigWasm = getSquadConfigWasm;
+window.setSquadConfigWasm = setSquadConfigWasm;
+window.resetSquadConfigWasm = resetSquadConfigWasm;
+window.addFlowObstacleWasm = addFlowObstacleWasm;
+window.addFlowCostWasm = addFlowCostWasm;
+window.clearFlowFieldWasm = clearFlowFieldWasm;
+window.setFlowCellSizeWasm = setFlowCellSizeWasm;
+window.getFlowDirectionWasm = getFlowDirectionWasm;
+window.getArchetype = getArchetype;
+window.loadArchetypesWasm = loadArchetypesWasm;
+window.AI_EVENT = AI_EVENT;
+window.AI_MODE = AI_MODE;
+window.AI_MODE_NAMES = AI_MODE_NAMES;
+window.setWaveAggressionWasm = setWaveAggressionWasm;
+window.setGlobalAggressionWasm = setGlobalAggressionWasm;
+window.setEnemyAggression = setEnemyAggression;
+window.getRammerConfigWasm = getRammerConfigWasm;
+window.setRammerConfigWasm = setRammerConfigWasm;
+window.resetRammerConfigWasm = resetRammerConfigWasm;
+window.setAISeedWasm = setAISeedWasm;
+window.getAIRngStateWasm = getAIRngStateWasm;
 window.aiWasmReady = () => aiWasmReady;
@@ -1,5 +1,4 @@
 use wasm_bindgen::prelude::*;
-use std::collections::HashSet;
 
 // Initialize WASM panic hook
 #[wasm_bindgen(start)]
@@ -8,6 +7,26 @@ pub fn init() {
     console_error_panic_hook::set_once();
 }
 
+// ========== FACTIONS ==========
+// Same relation matrix as ai-wasm (faction crate). Read-only here apart from
+// set_faction_matrix, which only setFactionRelation / resetFactionMatrix in gameAI.js call.
+// Relation ids: 0 allied, 1 neutral (hit but never targeted), 2 hostile.
+
+// Row-major MAX_FACTIONS x MAX_FACTIONS relation ids
+#[wasm_bindgen]
+pub fn get_faction_matrix() -> Vec<f64> {
+    faction::hostility().to_flat()
+}
+
+#[wasm_bindgen]
+pub fn set_faction_matrix(values: &[f64]) -> Result<(), JsValue> {
+    let hostility = faction::Hostility::from_flat(values).map_err(|e| JsValue::from_str(&e))?;
+    faction::set_hostility(hostility);
+    Ok(())
+}
+
+// Buffer layouts: projectiles [x, y, radius, damage, faction], enemies [x, y, radius, faction],
+// ship [x, y, radius], modules / powerups / stars [x, y, radius]
 #[wasm_bindgen]
 pub struct CollisionState {
     proj_buffer: Vec<f64>,
@@ -26,6 +45,12 @@ pub struct CollisionState {
     modulestar_collected: Vec<u32>, // Indices of stars collected
 }
 
+impl Default for CollisionState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl CollisionState {
     #[wasm_bindgen(constructor)]
@@ -114,130 +139,67 @@ impl CollisionState {
         let ship_y = self.ship_buffer[1];
         let ship_radius = self.ship_buffer[2];
 
-        // Track removed projectiles to avoid double-processing
-        let mut removed_projs = HashSet::new();
+        let hostility = faction::hostility();
 
-        // 1. Player/Module/Zombie Projectiles vs Enemies
+        // 1. Projectiles vs Ship/Modules (PLAYER faction), then Enemies in index order.
+        // A projectile hits whatever its faction is not allied with; each hits at most once.
         for i in 0..num_projs {
-            if removed_projs.contains(&i) { continue; }
-            
             let p_offset = i * 5;
-            let p_type = self.proj_buffer[p_offset + 4] as i32;
-
-            // Type 0=player, 1=module, 2=zombie projectiles hit enemies
-            if p_type == 0 || p_type == 1 || p_type == 2 {
-                let px = self.proj_buffer[p_offset];
-                let py = self.proj_buffer[p_offset + 1];
-                let pr = self.proj_buffer[p_offset + 2];
-                let p_damage = self.proj_buffer[p_offset + 3];
-
-                for j in 0..num_enemies {
-                    let e_offset = j * 4;
-                    let is_zombie = self.enemy_buffer[e_offset + 3] > 0.5;
-
-                    // Zombie projectiles don't hit zombies
-                    if p_type == 2 && is_zombie { continue; }
-
-                    let ex = self.enemy_buffer[e_offset];
-                    let ey = self.enemy_buffer[e_offset + 1];
-                    let er = self.enemy_buffer[e_offset + 2];
-
-                    let dist_sq = distance_squared(px, py, ex, ey);
-                    let radius_sum = pr + er;
-
-                    if dist_sq < radius_sum * radius_sum {
-                        self.projectile_hits.push(i as u32);
-                        removed_projs.insert(i);
-                        
-                        self.enemy_hits.push(j as f64);
-                        self.enemy_hits.push(p_damage);
-                        
-                        break;
-                    }
-                }
-            }
-        }
-
-        // 2. Enemy Projectiles vs Ship/Modules/Zombies
-        for i in 0..num_projs {
-            if removed_projs.contains(&i) { continue; }
-            
-            let p_offset = i * 5;
-            let p_type = self.proj_buffer[p_offset + 4] as i32;
-
-            if p_type == 3 { // Enemy projectile
-                let px = self.proj_buffer[p_offset];
-                let py = self.proj_buffer[p_offset + 1];
-                let pr = self.proj_buffer[p_offset + 2];
-                let p_damage = self.proj_buffer[p_offset + 3];
-                
-                let mut hit = false;
+            let px = self.proj_buffer[p_offset];
+            let py = self.proj_buffer[p_offset + 1];
+            let pr = self.proj_buffer[p_offset + 2];
+            let p_damage = self.proj_buffer[p_offset + 3];
+            let p_faction = faction::from_f64(self.proj_buffer[p_offset + 4]);
 
+            if hostility.can_hit(p_faction, faction::PLAYER) {
                 // Check Ship collision
                 let dist_sq_ship = distance_squared(px, py, ship_x, ship_y);
                 let r_sum_ship = pr + ship_radius;
-                
+
                 if dist_sq_ship < r_sum_ship * r_sum_ship {
                     if !shield_active {
                         self.ship_hit = true;
                         self.ship_damage += p_damage;
                     }
                     self.projectile_hits.push(i as u32);
-                    removed_projs.insert(i);
-                    hit = true;
+                    continue;
                 }
 
-                // Check Module collisions (if not already hit)
-                if !hit {
-                    for m in 0..num_modules {
-                        let m_offset = m * 3;
-                        let mx = self.mod_buffer[m_offset];
-                        let my = self.mod_buffer[m_offset + 1];
-                        let mr = self.mod_buffer[m_offset + 2];
-
-                        let dist_sq_mod = distance_squared(px, py, mx, my);
-                        let r_sum_mod = pr + mr;
-                        
-                        if dist_sq_mod < r_sum_mod * r_sum_mod {
-                            self.projectile_hits.push(i as u32);
-                            removed_projs.insert(i);
-                            hit = true;
-                            break;
-                        }
-                    }
+                // Modules absorb the shot without taking damage
+                let module_hit = self.mod_buffer[..num_modules * 3].chunks_exact(3).any(|m| {
+                    let r_sum_mod = pr + m[2];
+                    distance_squared(px, py, m[0], m[1]) < r_sum_mod * r_sum_mod
+                });
+                if module_hit {
+                    self.projectile_hits.push(i as u32);
+                    continue;
                 }
+            }
 
-                // Check Zombie collisions (if not already hit)
-                if !hit {
-                    for j in 0..num_enemies {
-                        let e_offset = j * 4;
-                        let is_zombie = self.enemy_buffer[e_offset + 3] > 0.5;
-                        
-                        if !is_zombie { continue; }
-                        
-                        let ex = self.enemy_buffer[e_offset];
-                        let ey = self.enemy_buffer[e_offset + 1];
-                        let er = self.enemy_buffer[e_offset + 2];
-                        
-                        let dist_sq_z = distance_squared(px, py, ex, ey);
-                        let r_sum_z = pr + er;
-                        
-                        if dist_sq_z < r_sum_z * r_sum_z {
-                            self.projectile_hits.push(i as u32);
-                            removed_projs.insert(i);
-                            
-                            self.enemy_hits.push(j as f64);
-                            self.enemy_hits.push(p_damage);
-                            
-                            hit = true;
-                            break;
-                        }
-                    }
+            for j in 0..num_enemies {
+                let e_offset = j * 4;
+                let e_faction = faction::from_f64(self.enemy_buffer[e_offset + 3]);
+                if !hostility.can_hit(p_faction, e_faction) { continue; }
+
+                let ex = self.enemy_buffer[e_offset];
+                let ey = self.enemy_buffer[e_offset + 1];
+                let er = self.enemy_buffer[e_offset + 2];
+
+                let dist_sq = distance_squared(px, py, ex, ey);
+                let radius_sum = pr + er;
+
+                if dist_sq < radius_sum * radius_sum {
+                    self.projectile_hits.push(i as u32);
+
+                    self.enemy_hits.push(j as f64);
+                    self.enemy_hits.push(p_damage);
+
+                    break;
                 }
             }
         }
 
-        // 3. PowerUp Collection by Ship
+        // 2. PowerUp Collection by Ship
         for i in 0..num_powerups {
             let offset = i * 3;
             let px = self.powerup_buffer[offset];
@@ -252,7 +214,7 @@ impl CollisionState {
             }
         }
 
-        // 4. ModuleStar Collection by Ship
+        // 3. ModuleStar Collection by Ship
         for i in 0..num_stars {
             let offset = i * 3;
             let sx = self.star_buffer[offset];
@@ -279,3 +241,51 @@ fn distance_squared(x1: f64, y1: f64, x2: f64, y2: f64) -> f64 {
 
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Ship at the origin; projectiles [x, y, radius, damage, faction], enemies [x, y, radius, faction]
+    fn check(projectiles: &[f64], enemies: &[f64], shield_active: bool) -> CollisionState {
+        let mut state = CollisionState::new();
+        state.ship_buffer.copy_from_slice(&[0.0, 0.0, 20.0]);
+        state.proj_buffer.extend_from_slice(projectiles);
+        state.enemy_buffer.extend_from_slice(enemies);
+        state.check_collisions_shared(projectiles.len() / 5, enemies.len() / 4, 0, 0, 0, shield_active);
+        state
+    }
+
+    #[test]
+    fn shots_skip_allies_and_hit_neutral_and_hostile_targets() {
+        let player = faction::PLAYER as f64;
+        let enemy = faction::ENEMY as f64;
+        let zombie = faction::ZOMBIE as f64;
+        let state = check(
+            &[
+                300.0, 300.0, 3.0, 5.0, player, // friendly fire on the zombie (neutral)
+                500.0, 300.0, 3.0, 7.0, enemy, // passes through the allied enemy
+                500.0, 300.0, 3.0, 9.0, zombie, // zombies shoot enemies
+            ],
+            &[300.0, 300.0, 15.0, zombie, 500.0, 300.0, 15.0, enemy],
+            false,
+        );
+        assert_eq!(state.projectile_hits, vec![0, 2]);
+        assert_eq!(state.enemy_hits, vec![0.0, 5.0, 1.0, 9.0]);
+    }
+
+    #[test]
+    fn enemy_shots_hit_the_ship_unless_shielded() {
+        let shot = [5.0, 0.0, 3.0, 4.0, faction::ENEMY as f64];
+        let state = check(&shot, &[], false);
+        assert!(state.ship_hit);
+        assert_eq!(state.ship_damage, 4.0);
+
+        let state = check(&shot, &[], true);
+        assert!(!state.ship_hit);
+        assert_eq!(state.projectile_hits, vec![0]);
+
+        let state = check(&[5.0, 0.0, 3.0, 4.0, faction::PLAYER as f64], &[], false);
+        assert!(!state.ship_hit && state.projectile_hits.is_empty());
+    }
+}
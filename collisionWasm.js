@@ -12,6 +12,7 @@ async function initWasm() {
 
         // Initialize persistent state
         collisionState = new wasmModule.CollisionState();
+        window.addFactionMatrixSink(values => module.set_faction_matrix(values));
 
         wasmReady = true;
         console.log('✅ WASM 碰撞檢測模塊已加載 (Shared Memory Mode)');
@@ -46,13 +47,7 @@ function prepareCollisionDataShared(projectiles, enemies, ship, modules, powerup
         projView[offset + 1] = proj.y;
         projView[offset + 2] = proj.radius;
         projView[offset + 3] = proj.damage;
-
-        let typeNum = 0;
-        if (proj.type === 'player') typeNum = 0;
-        else if (proj.type === 'module') typeNum = 1;
-        else if (proj.type === 'zombie') typeNum = 2;
-        else if (proj.type === 'enemy') typeNum = 3;
-        projView[offset + 4] = typeNum;
+        projView[offset + 4] = proj.faction;
     }
 
     // 2. Enemies
@@ -67,7 +62,7 @@ function prepareCollisionDataShared(projectiles, enemies, ship, modules, powerup
         enemyView[offset] = enemy.x;
         enemyView[offset + 1] = enemy.y;
         enemyView[offset + 2] = enemy.radius;
-        enemyView[offset + 3] = enemy.isZombie ? 1.0 : 0.0;
+        enemyView[offset + 3] = enemy.faction;
     }
 
     // 3. Ship
@@ -135,8 +130,6 @@ function checkCollisionsJS(projectiles, enemies, ship, modules, powerups, module
         free: () => {} // No-op for compatibility
     };
 
-    const removedProjs = new Set();
-
     // Helper function for distance squared
     const distSq = (x1, y1, x2, y2) => {
         const dx = x1 - x2;
@@ -144,101 +137,57 @@ function checkCollisionsJS(projectiles, enemies, ship, modules, powerups, module
         return dx * dx + dy * dy;
     };
 
-    // 1. Player/Module/Zombie Projectiles vs Enemies
-    for (let i = 0; i < projectiles.length; i++) {
-        if (removedProjs.has(i)) continue;
-        
-        const proj = projectiles[i];
-        
-        // Type check: player=0, module=1, zombie=2 projectiles hit enemies
-        if (proj.type === 'player' || proj.type === 'module' || proj.type === 'zombie') {
-            for (let j = 0; j < enemies.length; j++) {
-                const enemy = enemies[j];
-                
-                // Zombie projectiles don't hit zombies
-                if (proj.type === 'zombie' && enemy.isZombie) continue;
-                
-                const dist = distSq(proj.x, proj.y, enemy.x, enemy.y);
-                const radiusSum = proj.radius + enemy.radius;
-                
-                if (dist < radiusSum * radiusSum) {
-                    results.projectile_hits.push(i);
-                    removedProjs.add(i);
-                    
-                    results.enemy_hits.push(j);
-                    results.enemy_hits.push(proj.damage);
-                    
-                    break;
-                }
-            }
-        }
-    }
+    // Shots hit anything their faction is not allied with (same rule as collision-wasm)
+    const canHit = (from, to) => window.factionRelation(from, to) !== window.FACTION_RELATION.ALLIED;
 
-    // 2. Enemy Projectiles vs Ship/Modules/Zombies
+    // 1. Projectiles vs Ship/Modules (PLAYER faction), then Enemies in index order
     for (let i = 0; i < projectiles.length; i++) {
-        if (removedProjs.has(i)) continue;
-        
         const proj = projectiles[i];
-        
-        if (proj.type === 'enemy') {
-            let hit = false;
-            
+
+        if (canHit(proj.faction, window.FACTION.PLAYER)) {
             // Check Ship collision
             const shipDist = distSq(proj.x, proj.y, ship.x, ship.y);
             const shipRadiusSum = proj.radius + ship.radius;
-            
+
             if (shipDist < shipRadiusSum * shipRadiusSum) {
                 if (!gameState.shieldActive) {
                     results.ship_hit = true;
                     results.ship_damage += proj.damage;
                 }
                 results.projectile_hits.push(i);
-                removedProjs.add(i);
-                hit = true;
+                continue;
             }
-            
-            // Check Module collisions (if not already hit)
-            if (!hit) {
-                for (let m = 0; m < modules.length; m++) {
-                    const module = modules[m];
-                    const modDist = distSq(proj.x, proj.y, module.x, module.y);
-                    const modRadiusSum = proj.radius + module.radius;
-                    
-                    if (modDist < modRadiusSum * modRadiusSum) {
-                        results.projectile_hits.push(i);
-                        removedProjs.add(i);
-                        hit = true;
-                        break;
-                    }
-                }
+
+            // Modules absorb the shot without taking damage
+            const moduleHit = modules.some(module => {
+                const modRadiusSum = proj.radius + module.radius;
+                return distSq(proj.x, proj.y, module.x, module.y) < modRadiusSum * modRadiusSum;
+            });
+            if (moduleHit) {
+                results.projectile_hits.push(i);
+                continue;
             }
-            
-            // Check Zombie collisions (if not already hit)
-            if (!hit) {
-                for (let j = 0; j < enemies.length; j++) {
-                    const enemy = enemies[j];
-                    
-                    if (!enemy.isZombie) continue;
-                    
-                    const zombieDist = distSq(proj.x, proj.y, enemy.x, enemy.y);
-                    const zombieRadiusSum = proj.radius + enemy.radius;
-                    
-                    if (zombieDist < zombieRadiusSum * zombieRadiusSum) {
-                        results.projectile_hits.push(i);
-                        removedProjs.add(i);
-                        
-                        results.enemy_hits.push(j);
-                        results.enemy_hits.push(proj.damage);
-                        
-                        hit = true;
-                        break;
-                    }
-                }
+        }
+
+        for (let j = 0; j < enemies.length; j++) {
+            const enemy = enemies[j];
+            if (!canHit(proj.faction, enemy.faction)) continue;
+
+            const dist = distSq(proj.x, proj.y, enemy.x, enemy.y);
+            const radiusSum = proj.radius + enemy.radius;
+
+            if (dist < radiusSum * radiusSum) {
+                results.projectile_hits.push(i);
+
+                results.enemy_hits.push(j);
+                results.enemy_hits.push(proj.damage);
+
+                break;
             }
         }
     }
 
-    // 3. PowerUp Collection by Ship
+    // 2. PowerUp Collection by Ship
     for (let i = 0; i < powerups.length; i++) {
         const powerup = powerups[i];
         const dist = distSq(ship.x, ship.y, powerup.x, powerup.y);
@@ -249,7 +198,7 @@ function checkCollisionsJS(projectiles, enemies, ship, modules, powerups, module
         }
     }
 
-    // 4. ModuleStar Collection by Ship
+    // 3. ModuleStar Collection by Ship
     for (let i = 0; i < modulestars.length; i++) {
         const star = modulestars[i];
         const dist = distSq(ship.x, ship.y, star.x, star.y);
@@ -333,3 +282,4 @@ function checkCollisionsWasm(projectiles, enemies, ship, modules, powerups, modu
 window.initWasm = initWasm;
 window.checkCollisionsWasm = checkCollisionsWasm;
 window.wasmReady = () => wasmReady;
+
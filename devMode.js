@@ -223,6 +223,18 @@ class DevMode {
             }
         });
 
+        // Cycle the deployed module formation (ai-wasm only)
+        const formationNames = ['RING', 'LINE', 'WEDGE', 'ESCORT'];
+        let formationIndex = 0;
+        document.getElementById('devModuleFormation').addEventListener('click', () => {
+            if (!this.active) return;
+            const next = (formationIndex + 1) % formationNames.length;
+            if (window.setModuleFormationWasm && window.setModuleFormationWasm(formationNames[next])) {
+                formationIndex = next;
+                document.getElementById('devFormationName').textContent = formationNames[formationIndex];
+            }
+        });
+
         // Kill all enemies
         document.getElementById('devKillAll').addEventListener('click', () => {
             if (!this.active) return;
@@ -326,6 +338,17 @@ class DevMode {
                     zombieStatus.textContent = '❌ 未解鎖';
                     zombieStatus.style.color = '#f00';
                 }
+                // Count enemies per ai-wasm decision mode
+                if (window.AI_MODE_NAMES) {
+                    const counts = {};
+                    for (const enemy of enemies) {
+                        if (enemy.type !== 'rammer') continue;
+                        const name = window.AI_MODE_NAMES[enemy.aiMode] || 'cruise';
+                        counts[name] = (counts[name] || 0) + 1;
+                    }
+                    const summary = Object.entries(counts).map(([name, n]) => `${name} ${n}`).join(', ');
+                    document.getElementById('devAiModes').textContent = summary || '-';
+                }
             }
         }, 100);
     }
@@ -0,0 +1,177 @@
+// Faction allegiance shared by ai-wasm (targeting) and collision-wasm (hit filtering)
+// Every entity carries a faction id; a relation matrix decides who targets and who hits whom.
+// Each wasm module holds its own copy. The JS factionMatrix (gameAI.js) is the only writer:
+// setFactionRelation / resetFactionMatrix push the whole matrix to every module through
+// set_faction_matrix, so the copies cannot drift apart.
+use std::cell::RefCell;
+
+pub type FactionId = u8;
+
+pub const MAX_FACTIONS: usize = 8;
+
+// Built-in factions. Ship, modules and their shots are PLAYER.
+pub const PLAYER: FactionId = 0;
+pub const ENEMY: FactionId = 1;
+// Killed enemies raised to fight for the player
+pub const ZOMBIE: FactionId = 2;
+
+// How `from` treats `to`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    // Never targeted; shots pass through
+    Allied = 0,
+    // Never targeted, but stray shots still hit (friendly fire)
+    Neutral = 1,
+    // Targeted and hit
+    Hostile = 2,
+}
+
+impl Relation {
+    // Only the exact ids 0, 1 and 2; fractions, NaN and infinities are not relations
+    pub fn from_f64(v: f64) -> Option<Self> {
+        if !v.is_finite() || v.fract() != 0.0 {
+            return None;
+        }
+        match v as i32 {
+            0 => Some(Relation::Allied),
+            1 => Some(Relation::Neutral),
+            2 => Some(Relation::Hostile),
+            _ => None,
+        }
+    }
+}
+
+// Faction id stored in an f64 buffer slot; out-of-range values clamp into the table
+#[inline]
+pub fn from_f64(v: f64) -> FactionId {
+    v.clamp(0.0, (MAX_FACTIONS - 1) as f64) as FactionId
+}
+
+// table[from][to]; not necessarily symmetric
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hostility {
+    table: [[Relation; MAX_FACTIONS]; MAX_FACTIONS],
+}
+
+impl Default for Hostility {
+    // The original allegiance: enemies fight the player and zombies, zombies fight enemies,
+    // and player shots still hit zombies without modules aiming at them
+    fn default() -> Self {
+        let mut hostility = Hostility { table: [[Relation::Allied; MAX_FACTIONS]; MAX_FACTIONS] };
+        hostility.set(PLAYER, ENEMY, Relation::Hostile);
+        hostility.set(PLAYER, ZOMBIE, Relation::Neutral);
+        hostility.set(ENEMY, PLAYER, Relation::Hostile);
+        hostility.set(ENEMY, ZOMBIE, Relation::Hostile);
+        hostility.set(ZOMBIE, ENEMY, Relation::Hostile);
+        hostility
+    }
+}
+
+impl Hostility {
+    #[inline]
+    pub fn relation(&self, from: FactionId, to: FactionId) -> Relation {
+        self.table
+            .get(from as usize)
+            .and_then(|row| row.get(to as usize))
+            .copied()
+            .unwrap_or(Relation::Allied)
+    }
+
+    // Whether `from` picks `to` as a target
+    #[inline]
+    pub fn is_hostile(&self, from: FactionId, to: FactionId) -> bool {
+        self.relation(from, to) == Relation::Hostile
+    }
+
+    // Whether shots from `from` collide with `to`
+    #[inline]
+    pub fn can_hit(&self, from: FactionId, to: FactionId) -> bool {
+        self.relation(from, to) != Relation::Allied
+    }
+
+    // Returns false for an out-of-range faction
+    pub fn set(&mut self, from: FactionId, to: FactionId, relation: Relation) -> bool {
+        match self.table.get_mut(from as usize).and_then(|row| row.get_mut(to as usize)) {
+            Some(slot) => {
+                *slot = relation;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Row-major MAX_FACTIONS x MAX_FACTIONS relation ids
+    pub fn to_flat(&self) -> Vec<f64> {
+        self.table.iter().flatten().map(|&r| r as i32 as f64).collect()
+    }
+
+    pub fn from_flat(values: &[f64]) -> Result<Self, String> {
+        if values.len() != MAX_FACTIONS * MAX_FACTIONS {
+            return Err(format!(
+                "faction matrix needs {} values, got {}",
+                MAX_FACTIONS * MAX_FACTIONS,
+                values.len()
+            ));
+        }
+        let mut hostility = Hostility { table: [[Relation::Allied; MAX_FACTIONS]; MAX_FACTIONS] };
+        for (i, &v) in values.iter().enumerate() {
+            let relation = Relation::from_f64(v)
+                .ok_or_else(|| format!("faction matrix[{}] = {} is not a relation (0-2)", i, v))?;
+            hostility.table[i / MAX_FACTIONS][i % MAX_FACTIONS] = relation;
+        }
+        Ok(hostility)
+    }
+}
+
+thread_local! {
+    static HOSTILITY: RefCell<Hostility> = RefCell::new(Hostility::default());
+}
+
+pub fn hostility() -> Hostility {
+    HOSTILITY.with(|h| *h.borrow())
+}
+
+pub fn set_hostility(hostility: Hostility) {
+    HOSTILITY.with(|h| *h.borrow_mut() = hostility);
+}
+
+#[inline]
+pub fn is_hostile(from: FactionId, to: FactionId) -> bool {
+    HOSTILITY.with(|h| h.borrow().is_hostile(from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relations_must_be_exact_ids() {
+        assert_eq!(Relation::from_f64(2.0), Some(Relation::Hostile));
+        for v in [1.5, 0.2, -0.5, 3.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(Relation::from_f64(v), None, "{}", v);
+        }
+    }
+
+    #[test]
+    fn flat_matrix_round_trips_and_rejects_malformed_input() {
+        let default = Hostility::default();
+        assert_eq!(Hostility::from_flat(&default.to_flat()), Ok(default));
+
+        let mut values = default.to_flat();
+        values[3] = 1.5;
+        assert!(Hostility::from_flat(&values).is_err());
+        values[3] = f64::NAN;
+        assert!(Hostility::from_flat(&values).is_err());
+        assert!(Hostility::from_flat(&values[1..]).is_err());
+    }
+
+    #[test]
+    fn neutral_is_hit_but_not_targeted() {
+        let hostility = Hostility::default();
+        assert!(hostility.can_hit(PLAYER, ZOMBIE));
+        assert!(!hostility.is_hostile(PLAYER, ZOMBIE));
+        assert!(hostility.can_hit(PLAYER, ENEMY) && hostility.is_hostile(PLAYER, ENEMY));
+        assert!(!hostility.can_hit(ENEMY, ENEMY));
+        assert!(!hostility.can_hit(ZOMBIE, PLAYER));
+    }
+}
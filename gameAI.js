@@ -27,6 +27,66 @@ const RAMMER = {
 
 window.RAMMER = RAMMER;
 
+// ==== Factions (mirrors the faction crate used by ai-wasm and collision-wasm) ====
+const FACTION = { PLAYER: 0, ENEMY: 1, ZOMBIE: 2 };
+const MAX_FACTIONS = 8;
+// ALLIED: ignored; NEUTRAL: shots hit but never targeted; HOSTILE: targeted and hit
+const FACTION_RELATION = { ALLIED: 0, NEUTRAL: 1, HOSTILE: 2 };
+const PROJECTILE_FACTION = { player: FACTION.PLAYER, module: FACTION.PLAYER, zombie: FACTION.ZOMBIE, enemy: FACTION.ENEMY };
+
+// Row-major [from * MAX_FACTIONS + to], same defaults as Hostility::default().
+// This is the only writable copy: ai-wasm and collision-wasm each hold a mirror that is
+// replaced wholesale from here, so change relations only through setFactionRelation /
+// resetFactionMatrix.
+const factionMatrix = new Array(MAX_FACTIONS * MAX_FACTIONS).fill(FACTION_RELATION.ALLIED);
+// set_faction_matrix of every loaded wasm module (registered by aiWasm.js / collisionWasm.js)
+const factionMatrixSinks = [];
+
+function pushFactionMatrix() {
+    const values = new Float64Array(factionMatrix);
+    for (const push of factionMatrixSinks) push(values);
+}
+
+// Called once per wasm module on load; the module gets the current matrix right away
+function addFactionMatrixSink(push) {
+    factionMatrixSinks.push(push);
+    push(new Float64Array(factionMatrix));
+}
+
+function resetFactionMatrix() {
+    factionMatrix.fill(FACTION_RELATION.ALLIED);
+    factionMatrix[FACTION.PLAYER * MAX_FACTIONS + FACTION.ENEMY] = FACTION_RELATION.HOSTILE;
+    factionMatrix[FACTION.PLAYER * MAX_FACTIONS + FACTION.ZOMBIE] = FACTION_RELATION.NEUTRAL;
+    factionMatrix[FACTION.ENEMY * MAX_FACTIONS + FACTION.PLAYER] = FACTION_RELATION.HOSTILE;
+    factionMatrix[FACTION.ENEMY * MAX_FACTIONS + FACTION.ZOMBIE] = FACTION_RELATION.HOSTILE;
+    factionMatrix[FACTION.ZOMBIE * MAX_FACTIONS + FACTION.ENEMY] = FACTION_RELATION.HOSTILE;
+    pushFactionMatrix();
+}
+resetFactionMatrix();
+
+function factionRelation(from, to) {
+    const relation = factionMatrix[from * MAX_FACTIONS + to];
+    return relation === undefined ? FACTION_RELATION.ALLIED : relation;
+}
+
+// Update the JS matrix and push it to both wasm modules so targeting and hit filtering agree
+function setFactionRelation(from, to, relation) {
+    if (from < 0 || from >= MAX_FACTIONS || to < 0 || to >= MAX_FACTIONS) return false;
+    if (!Object.values(FACTION_RELATION).includes(relation)) return false;
+    factionMatrix[from * MAX_FACTIONS + to] = relation;
+    pushFactionMatrix();
+    return true;
+}
+
+window.FACTION = FACTION;
+window.FACTION_RELATION = FACTION_RELATION;
+window.PROJECTILE_FACTION = PROJECTILE_FACTION;
+window.factionMatrix = factionMatrix;
+window.factionRelation = factionRelation;
+window.setFactionRelation = setFactionRelation;
+window.resetFactionMatrix = resetFactionMatrix;
+window.addFactionMatrixSink = addFactionMatrixSink;
+
 // ==== Uniform grid for enemy neighbor queries (exported) ====
 const ENEMY_CELL = 128;               // tune 96~160 depending on density
 const __enemyGrid = new Map();        // key: "cx,cy" -> array of indices
@@ -77,6 +137,23 @@ window.queryEnemyNeighbors = function (x, y) {
         return queryEnemyNeighborsJS(x, y);
     }
 };
+// ==== Squads (ai-wasm/src/squad.rs): members fly in formation behind slot 0 ====
+const SQUAD_SHAPE = { RING: 0, V: 1, LINE: 2, PINCER: 3 };
+let nextSquadId = 1;
+
+// Put `members` into a new squad; the first one leads. Returns the squad id.
+function formSquad(members, shape = SQUAD_SHAPE.RING) {
+    const id = nextSquadId++;
+    members.forEach((enemy, slot) => {
+        enemy.squad = id;
+        enemy.squadSlot = slot;
+        enemy.squadShape = shape;
+    });
+    return id;
+}
+
+window.SQUAD_SHAPE = SQUAD_SHAPE;
+window.formSquad = formSquad;
 // ============================================================
 
 // Enemy class with advanced AI behaviors
@@ -85,11 +162,15 @@ class Enemy {
         this.x = x;
         this.y = y;
         this.type = type;
-        this.isZombie = isZombie;
+        this.faction = isZombie ? FACTION.ZOMBIE : FACTION.ENEMY;
         this.isStealth = isStealth;
         this.splitLevel = splitLevel;
 
-        if (type === 'basic') {
+        // Stats come from the ai-wasm archetype list when it is loaded
+        const archetype = window.getArchetype ? window.getArchetype(type) : null;
+        if (archetype) {
+            this.radius = archetype.radius; this.hp = archetype.hp; this.speed = archetype.speed;
+        } else if (type === 'basic') {
             this.radius = 15; this.hp = 50; this.speed = 2;
         } else if (type === 'elite') {
             this.radius = 25; this.hp = 100; this.speed = 1;
@@ -112,11 +193,28 @@ class Enemy {
         this.pulsePhase = 0;
         this.zombieLifetime = isZombie ? 480 : 0;
         this.stealthWavePhase = Math.random() * Math.PI * 2;
+        this.visibility = isStealth ? 0.2 : 1.0; // driven by ai-wasm when active
+        this.aiMode = 0; // AI_MODE, driven by ai-wasm when active
+        this.stateFrames = 0;
+        this.squad = 0; // squad id (0 = none), see formSquad
+        this.squadSlot = 0; // 0 = leader
+        this.squadShape = SQUAD_SHAPE.RING;
+        this.speedScale = 1.0; // multiplier on speed (wave ramp), sent to ai-wasm at offset 27
+    }
+
+    // Zombie conversion is a faction change
+    get isZombie() {
+        return this.faction === FACTION.ZOMBIE;
+    }
+
+    set isZombie(value) {
+        this.faction = value ? FACTION.ZOMBIE : FACTION.ENEMY;
     }
 
     update(ship, enemies, projectiles, particles, getParticle, supernovaEffects, ExploderExplosion, gameState, clampEnergy, canvas) {
         const TWO_PI = Math.PI * 2;
         const HALF_PI = Math.PI / 2;
+        const speed = this.speed * this.speedScale;
 
         // Zombie lifetime countdown
         if (this.isZombie) {
@@ -129,8 +227,8 @@ class Enemy {
 
         if (this.type === 'basic') {
             this.angle += 0.02;
-            this.x += Math.cos(this.angle) * this.speed;
-            this.y += Math.sin(this.angle) * this.speed;
+            this.x += Math.cos(this.angle) * speed;
+            this.y += Math.sin(this.angle) * speed;
         } else if (this.type === 'elite') {
             // Target nearest non-zombie enemy if zombie, otherwise target ship
             let targetX, targetY;
@@ -148,7 +246,7 @@ class Enemy {
                 targetY = ship.y;
             }
             const dx = targetX - this.x, dy = targetY - this.y, dist = Math.hypot(dx, dy);
-            if (dist > 200) { this.x += (dx / dist) * this.speed; this.y += (dy / dist) * this.speed; }
+            if (dist > 200) { this.x += (dx / dist) * speed; this.y += (dy / dist) * speed; }
         } else if (this.type === 'exploder') {
             this.pulsePhase += 0.1;
 
@@ -169,8 +267,8 @@ class Enemy {
 
             const dx = targetX - this.x, dy = targetY - this.y, dist = Math.hypot(dx, dy);
             if (dist > 0) {
-                this.x += (dx / dist) * this.speed;
-                this.y += (dy / dist) * this.speed;
+                this.x += (dx / dist) * speed;
+                this.y += (dy / dist) * speed;
             }
 
             if (this.isZombie) {
@@ -625,7 +723,8 @@ class Enemy {
                     ctx.stroke();
                 }
 
-                ctx.globalAlpha = 0.2 + Math.sin(this.stealthWavePhase) * 0.08;
+                // ai-wasm raises visibility while the rammer charges
+                ctx.globalAlpha = Math.max(this.visibility, 0.2 + Math.sin(this.stealthWavePhase) * 0.08);
                 ctx.strokeStyle = `rgba(${stealthColor.r + 50}, ${stealthColor.g + 30}, ${stealthColor.b + 20}, 0.5)`;
                 ctx.lineWidth = 1.5;
                 ctx.shadowBlur = 20;
@@ -676,6 +775,15 @@ class Enemy {
                 ctx.lineWidth = 2;
                 ctx.stroke();
             }
+
+            // Charge telegraph: flashing warning ring while the rammer winds up
+            if (window.AI_MODE && this.aiMode === window.AI_MODE.TELEGRAPH) {
+                ctx.strokeStyle = `rgba(255, 60, 0, ${0.5 + Math.sin(this.stateFrames * 1.2) * 0.4})`;
+                ctx.lineWidth = 3;
+                ctx.beginPath();
+                ctx.arc(0, 0, this.radius * 2.6, 0, TWO_PI);
+                ctx.stroke();
+            }
         } else if (this.type === 'exploder') {
             ctx.rotate(this.pulsePhase);
             const pulseScale = 1 + Math.sin(this.pulsePhase * 3) * 0.15;
@@ -739,6 +847,17 @@ class Enemy {
             ctx.lineWidth = 2;
             ctx.stroke();
         }
+
+        // Squad leader: dashed ring so the formation's anchor is readable
+        if (this.squad && this.squadSlot === 0) {
+            ctx.setLineDash([4, 4]);
+            ctx.strokeStyle = 'rgba(255, 255, 255, 0.6)';
+            ctx.lineWidth = 1.5;
+            ctx.beginPath();
+            ctx.arc(0, 0, this.radius * 1.6, 0, TWO_PI);
+            ctx.stroke();
+            ctx.setLineDash([]);
+        }
         ctx.restore();
     }
 }
@@ -1,3 +1,4 @@
+use rng::random;
 use wasm_bindgen::prelude::*;
 
 // Initialize WASM panic hook for better error messages
@@ -11,6 +12,19 @@ pub fn init() {
 const PARTICLE_STRIDE: usize = 6;
 const DAMPING: f64 = 0.95;
 
+/// Reseed the particle RNG
+/// Passing a value from get_rng_state resumes the sequence from that point
+#[wasm_bindgen]
+pub fn set_seed(seed: u32) {
+    rng::seed(seed);
+}
+
+/// Current particle RNG state
+#[wasm_bindgen]
+pub fn get_rng_state() -> u32 {
+    rng::state()
+}
+
 /// Update particles and return only alive ones
 /// Input: flat array [x, y, vx, vy, life, hue, x, y, vx, vy, life, hue, ...]
 /// Output: flat array of alive particles in the same format
@@ -73,14 +87,14 @@ pub fn create_explosion_particles(
     
     for _ in 0..count {
         // Random velocity
-        let vx = (js_sys::Math::random() - 0.5) * 10.0;
-        let vy = (js_sys::Math::random() - 0.5) * 10.0;
+        let vx = (random() - 0.5) * 10.0;
+        let vy = (random() - 0.5) * 10.0;
         
         // Color: zombie = green (100-140 hue), normal = red-yellow (0-60 hue)
         let hue = if is_zombie {
-            100.0 + js_sys::Math::random() * 40.0
+            100.0 + random() * 40.0
         } else {
-            js_sys::Math::random() * 60.0
+            random() * 60.0
         };
         
         particles.push(x);
@@ -121,8 +121,22 @@ function createExplosionParticlesWasm(x, y, count, isZombie, getParticle) {
     }
 }
 
+// Seed the particle RNG so explosions can be reproduced
+function setParticleSeedWasm(seed) {
+    if (!particleWasmReady || !particleWasmModule) return false;
+    particleWasmModule.set_seed(seed >>> 0);
+    return true;
+}
+
+function getParticleRngStateWasm() {
+    if (!particleWasmReady || !particleWasmModule) return null;
+    return particleWasmModule.get_rng_state();
+}
+
 // Export functions
 window.initParticleWasm = initParticleWasm;
 window.updateParticlesWasm = updateParticlesWasm;
 window.createExplosionParticlesWasm = createExplosionParticlesWasm;
+window.setParticleSeedWasm = setParticleSeedWasm;
+window.getParticleRngStateWasm = getParticleRngStateWasm;
 window.particleWasmReady = () => particleWasmReady;
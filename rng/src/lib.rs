@@ -0,0 +1,51 @@
+// Seedable PRNG (mulberry32) shared by ai-wasm and particle-wasm
+// Each wasm module links its own copy, so the two streams are seeded independently.
+use std::cell::RefCell;
+
+pub const DEFAULT_SEED: u32 = 0x9E37_79B9;
+
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        Self { state: seed }
+    }
+
+    #[inline]
+    pub fn state(&self) -> u32 {
+        self.state
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = self.state.wrapping_add(0x6D2B_79F5);
+        let mut t = self.state;
+        t = (t ^ (t >> 15)).wrapping_mul(t | 1);
+        t ^= t.wrapping_add((t ^ (t >> 7)).wrapping_mul(t | 61));
+        t ^ (t >> 14)
+    }
+
+    // Uniform value in [0, 1), drop-in for Math.random()
+    #[inline]
+    pub fn next_f64(&mut self) -> f64 {
+        self.next_u32() as f64 / 4_294_967_296.0
+    }
+}
+
+thread_local! {
+    static RNG: RefCell<Rng> = RefCell::new(Rng::new(DEFAULT_SEED));
+}
+
+// Every random draw in the simulation goes through here
+pub fn random() -> f64 {
+    RNG.with(|rng| rng.borrow_mut().next_f64())
+}
+
+pub fn seed(seed: u32) {
+    RNG.with(|rng| *rng.borrow_mut() = Rng::new(seed));
+}
+
+pub fn state() -> u32 {
+    RNG.with(|rng| rng.borrow().state())
+}
@@ -1,7 +1,7 @@
 // ============ SHIP SYSTEM ============
 // MotherShip and Module classes with related configurations
 
-// Module movement tunables
+// Module movement tunables (JS fallback; ai-wasm uses FormationConfig with the same defaults)
 const MODULE = {
     LERP: 0.28,        // faster default follow responsiveness
     SPLIT_LERP: 0.24,  // faster autonomous tracking
@@ -10,6 +10,9 @@ const MODULE = {
     MAX_SPEED: 10      // slight cap increase to support quicker turns
 };
 
+// Module weapon
+const MODULE_BULLET_SPEED = 10;
+
 // Ship configuration constants
 const BASE_MODULES = 3;           // initial module count
 const MAX_MODULES = 6;            // base(3) + up to +3 from purple stars
@@ -35,16 +38,31 @@ class MotherShip {
         this.rotation = 0;
         this.targetX = x;
         this.targetY = y;
+        this.vx = 0; // per-frame motion, used by ai-wasm rammer intercepts
+        this.vy = 0;
         this.isSplit = false;
         this.splitCooldown = 0;
     }
 
     update() {
-        this.x += (this.targetX - this.x) * 0.15;
-        this.y += (this.targetY - this.y) * 0.15;
+        this.vx = (this.targetX - this.x) * 0.15;
+        this.vy = (this.targetY - this.y) * 0.15;
+        this.x += this.vx;
+        this.y += this.vy;
         this.rotation += 0.02;
         if (this.splitCooldown > 0) this.splitCooldown--;
 
+        // Module formation and combat run in ai-wasm when it is loaded
+        const wasmReady = window.aiWasmReady && window.aiWasmReady();
+        if (wasmReady && window.updateModuleFormationWasm(this.modules, window.enemies || [], this, this.isSplit)) {
+            const shots = window.updateModuleControllerWasm(this.modules, window.enemies || [], this, this.isSplit) || [];
+            const projectiles = window.projectiles || [];
+            for (const shot of shots) {
+                projectiles.push(new window.Projectile(shot.x, shot.y, shot.vx, shot.vy, 'module', shot.damage));
+            }
+            return;
+        }
+
         this.modules.forEach((module, index) => {
             if (!this.isSplit) {
                 const TWO_PI = Math.PI * 2;
@@ -186,6 +204,7 @@ class Module {
         this.recalling = 0;
     }
 
+    // JS fallback; with ai-wasm loaded ModuleFormation / ModuleController drive modules
     update() {
         // Tunable, capped module movement
         const lerp = (this.recalling > 0) ? MODULE.RECALL_LERP : (this.autonomous ? MODULE.SPLIT_LERP : MODULE.LERP);
@@ -234,7 +253,7 @@ class Module {
         if (candIdx.length > 0) {
             for (let k = 0; k < candIdx.length; k++) {
                 const e = enemies[candIdx[k]];
-                if (!e || e.isZombie) continue;
+                if (!e || factionRelation(FACTION.PLAYER, e.faction) !== FACTION_RELATION.HOSTILE) continue;
                 const dx = e.x - this.x, dy = e.y - this.y;
                 const d2 = dx * dx + dy * dy;
                 if (d2 < bestD2) {
@@ -248,7 +267,7 @@ class Module {
         if (!nearest) {
             for (let i = 0; i < enemies.length; i++) {
                 const e = enemies[i];
-                if (!e || e.isZombie) continue;
+                if (!e || factionRelation(FACTION.PLAYER, e.faction) !== FACTION_RELATION.HOSTILE) continue;
                 const dx = e.x - this.x, dy = e.y - this.y;
                 const d2 = dx * dx + dy * dy;
                 if (d2 < bestD2) {
@@ -265,7 +284,7 @@ class Module {
         const projectiles = window.projectiles || [];
 
         // Predictive targeting - lead the target based on its velocity
-        const bulletSpeed = 10;
+        const bulletSpeed = MODULE_BULLET_SPEED;
 
         // Get target velocity (vx, vy for rammer/elite/exploder, or derive from angle for basic)
         const targetVx = target.vx || 0;